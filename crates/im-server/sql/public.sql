@@ -25,6 +25,7 @@ CREATE TABLE im_group_message (
 CREATE INDEX idx_group_msg_group ON im_group_message (group_id);
 CREATE INDEX idx_from_id ON im_group_message (from_id);
 CREATE INDEX idx_group_msg_sequence ON im_group_message (sequence);
+CREATE INDEX idx_group_msg_group_sequence ON im_group_message (group_id, sequence);
 
 -- 添加表注释
 COMMENT ON TABLE im_group_message IS '群聊消息表';
@@ -38,7 +39,7 @@ COMMENT ON COLUMN im_group_message.message_time IS '发送时间';
 COMMENT ON COLUMN im_group_message.message_content_type IS '消息类型';
 COMMENT ON COLUMN im_group_message.extra IS '扩展字段';
 COMMENT ON COLUMN im_group_message.del_flag IS '删除标识（1正常，0删除）';
-COMMENT ON COLUMN im_group_message.sequence IS '消息序列';
+COMMENT ON COLUMN im_group_message.sequence IS '消息序列（群内严格递增）';
 COMMENT ON COLUMN im_group_message.message_random IS '随机标识';
 COMMENT ON COLUMN im_group_message.create_time IS '创建时间';
 COMMENT ON COLUMN im_group_message.update_time IS '更新时间';
@@ -159,6 +160,7 @@ CREATE TABLE im_friendship (
 
 CREATE INDEX idx_im_friendship_owner_id ON im_friendship (owner_id);
 CREATE INDEX idx_im_friendship_to_id ON im_friendship (to_id);
+CREATE INDEX idx_im_friendship_owner_sequence ON im_friendship (owner_id, sequence);
 
 COMMENT ON TABLE im_friendship IS '好友关系表';
 COMMENT ON COLUMN im_friendship.owner_id IS '用户ID';
@@ -168,7 +170,7 @@ COMMENT ON COLUMN im_friendship.del_flag IS '删除标识（1正常，0删除）
 COMMENT ON COLUMN im_friendship.black IS '黑名单状态（1正常，2拉黑）';
 COMMENT ON COLUMN im_friendship.create_time IS '创建时间';
 COMMENT ON COLUMN im_friendship.update_time IS '更新时间';
-COMMENT ON COLUMN im_friendship.sequence IS '序列号';
+COMMENT ON COLUMN im_friendship.sequence IS '序列号（按 owner_id 单调递增，每次变更更新）';
 COMMENT ON COLUMN im_friendship.black_sequence IS '黑名单序列号';
 COMMENT ON COLUMN im_friendship.add_source IS '好友来源';
 COMMENT ON COLUMN im_friendship.extra IS '扩展字段';
@@ -198,6 +200,7 @@ CREATE TABLE im_friendship_request (
 CREATE INDEX idx_im_friendship_request_from_id ON im_friendship_request (from_id);
 CREATE INDEX idx_im_friendship_request_to_id ON im_friendship_request (to_id);
 CREATE INDEX idx_im_friendship_request_to_id_status ON im_friendship_request (to_id, approve_status);
+CREATE INDEX idx_im_friendship_request_status_create_time ON im_friendship_request (approve_status, create_time);
 
 COMMENT ON TABLE im_friendship_request IS '好友请求表';
 COMMENT ON COLUMN im_friendship_request.id IS '请求ID';
@@ -207,7 +210,7 @@ COMMENT ON COLUMN im_friendship_request.remark IS '备注';
 COMMENT ON COLUMN im_friendship_request.read_status IS '是否已读（1已读）';
 COMMENT ON COLUMN im_friendship_request.add_source IS '好友来源';
 COMMENT ON COLUMN im_friendship_request.message IS '好友验证信息';
-COMMENT ON COLUMN im_friendship_request.approve_status IS '审批状态（1同意，2拒绝）';
+COMMENT ON COLUMN im_friendship_request.approve_status IS '审批状态（0待处理，1同意，2拒绝，3已过期，4已撤回）';
 COMMENT ON COLUMN im_friendship_request.create_time IS '创建时间';
 COMMENT ON COLUMN im_friendship_request.update_time IS '更新时间';
 COMMENT ON COLUMN im_friendship_request.sequence IS '序列号';
@@ -251,7 +254,7 @@ COMMENT ON COLUMN im_user_data.gender IS '性别';
 COMMENT ON COLUMN im_user_data.birthday IS '生日';
 COMMENT ON COLUMN im_user_data.location IS '地址';
 COMMENT ON COLUMN im_user_data.self_signature IS '个性签名';
-COMMENT ON COLUMN im_user_data.friend_allow_type IS '加好友验证类型（1无需验证，2需要验证）';
+COMMENT ON COLUMN im_user_data.friend_allow_type IS '加好友验证类型（1无需验证，2需要验证，3不允许任何人添加）';
 COMMENT ON COLUMN im_user_data.forbidden_flag IS '禁用标识（1禁用）';
 COMMENT ON COLUMN im_user_data.disable_add_friend IS '管理员禁止添加好友：0未禁用，1已禁用';
 COMMENT ON COLUMN im_user_data.silent_flag IS '禁言标识（1禁言）';
@@ -379,6 +382,7 @@ CREATE TABLE im_group_member (
   role integer NOT NULL,
   speak_date timestamptz DEFAULT NULL,
   mute smallint NOT NULL,
+  mute_end_time timestamptz DEFAULT NULL,
   alias varchar(100) DEFAULT NULL,
   join_time timestamptz DEFAULT CURRENT_TIMESTAMP,
   leave_time timestamptz DEFAULT NULL,
@@ -406,6 +410,7 @@ COMMENT ON COLUMN im_group_member.member_id IS '成员用户ID';
 COMMENT ON COLUMN im_group_member.role IS '群成员角色（0普通成员，1管理员，2群主）';
 COMMENT ON COLUMN im_group_member.speak_date IS '最后发言时间';
 COMMENT ON COLUMN im_group_member.mute IS '是否禁言（1不禁言，0禁言）';
+COMMENT ON COLUMN im_group_member.mute_end_time IS '禁言截止时间（为空表示永久禁言）';
 COMMENT ON COLUMN im_group_member.alias IS '群昵称';
 COMMENT ON COLUMN im_group_member.join_time IS '加入时间';
 COMMENT ON COLUMN im_group_member.leave_time IS '离开时间';
@@ -498,3 +503,347 @@ COMMENT ON COLUMN im_chat.create_time IS '创建时间';
 COMMENT ON COLUMN im_chat.update_time IS '更新时间';
 COMMENT ON COLUMN im_chat.del_flag IS '删除标识（1正常，0删除）';
 COMMENT ON COLUMN im_chat.version IS '版本信息';
+
+--
+-- Table structure for table user_totp
+--
+
+DROP TABLE IF EXISTS user_totp;
+CREATE TABLE user_totp (
+  open_id varchar(50) NOT NULL,
+  secret varchar(64) NOT NULL,
+  enabled smallint NOT NULL DEFAULT 0,
+  create_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  update_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (open_id)
+);
+
+-- 添加表注释
+COMMENT ON TABLE user_totp IS '用户两步验证（TOTP）表';
+
+-- 添加字段注释
+COMMENT ON COLUMN user_totp.open_id IS '用户open_id';
+COMMENT ON COLUMN user_totp.secret IS 'TOTP密钥（Base32）';
+COMMENT ON COLUMN user_totp.enabled IS '是否启用：0待确认，1已启用';
+COMMENT ON COLUMN user_totp.create_time IS '创建时间';
+COMMENT ON COLUMN user_totp.update_time IS '更新时间';
+
+--
+-- Table structure for table user_recovery_code
+--
+
+DROP TABLE IF EXISTS user_recovery_code;
+CREATE TABLE user_recovery_code (
+  id bigserial NOT NULL,
+  open_id varchar(50) NOT NULL,
+  code_hash varchar(255) NOT NULL,
+  used_time timestamptz DEFAULT NULL,
+  create_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (id)
+);
+
+-- 创建索引
+CREATE INDEX idx_recovery_code_open_id ON user_recovery_code (open_id);
+
+-- 添加表注释
+COMMENT ON TABLE user_recovery_code IS '两步验证恢复码表（一次性）';
+
+-- 添加字段注释
+COMMENT ON COLUMN user_recovery_code.id IS '主键ID';
+COMMENT ON COLUMN user_recovery_code.open_id IS '用户open_id';
+COMMENT ON COLUMN user_recovery_code.code_hash IS '恢复码哈希';
+COMMENT ON COLUMN user_recovery_code.used_time IS '使用时间，NULL表示未使用';
+COMMENT ON COLUMN user_recovery_code.create_time IS '创建时间';
+
+--
+-- Table structure for table im_friend_category
+--
+
+DROP TABLE IF EXISTS im_friend_category;
+CREATE TABLE im_friend_category (
+  category_id varchar(50) NOT NULL,
+  owner_id varchar(50) NOT NULL,
+  name varchar(50) NOT NULL,
+  sequence bigint DEFAULT NULL,
+  create_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  update_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  del_flag smallint NOT NULL DEFAULT 1,
+  version bigint DEFAULT NULL,
+  PRIMARY KEY (category_id)
+);
+
+-- 创建索引
+CREATE INDEX idx_friend_category_owner ON im_friend_category (owner_id);
+
+-- 添加表注释
+COMMENT ON TABLE im_friend_category IS '好友分组表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_friend_category.category_id IS '分组ID';
+COMMENT ON COLUMN im_friend_category.owner_id IS '所有者用户ID';
+COMMENT ON COLUMN im_friend_category.name IS '分组名称';
+COMMENT ON COLUMN im_friend_category.sequence IS '序列号';
+COMMENT ON COLUMN im_friend_category.create_time IS '创建时间';
+COMMENT ON COLUMN im_friend_category.update_time IS '更新时间';
+COMMENT ON COLUMN im_friend_category.del_flag IS '删除标识（1正常，0删除）';
+COMMENT ON COLUMN im_friend_category.version IS '版本信息';
+
+--
+-- Table structure for table im_friend_category_member
+--
+
+DROP TABLE IF EXISTS im_friend_category_member;
+CREATE TABLE im_friend_category_member (
+  category_id varchar(50) NOT NULL,
+  owner_id varchar(50) NOT NULL,
+  to_id varchar(50) NOT NULL,
+  create_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (category_id, to_id)
+);
+
+-- 创建索引
+CREATE INDEX idx_friend_category_member_owner_to ON im_friend_category_member (owner_id, to_id);
+
+-- 添加表注释
+COMMENT ON TABLE im_friend_category_member IS '好友分组成员表（一个好友可属于多个分组）';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_friend_category_member.category_id IS '分组ID';
+COMMENT ON COLUMN im_friend_category_member.owner_id IS '所有者用户ID';
+COMMENT ON COLUMN im_friend_category_member.to_id IS '好友用户ID';
+COMMENT ON COLUMN im_friend_category_member.create_time IS '创建时间';
+
+--
+-- Table structure for table im_friendship_sequence
+--
+
+DROP TABLE IF EXISTS im_friendship_sequence;
+CREATE TABLE im_friendship_sequence (
+  owner_id varchar(50) NOT NULL,
+  sequence bigint NOT NULL DEFAULT 0,
+  PRIMARY KEY (owner_id)
+);
+
+-- 添加表注释
+COMMENT ON TABLE im_friendship_sequence IS '好友关系序列号表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_friendship_sequence.owner_id IS '用户ID';
+COMMENT ON COLUMN im_friendship_sequence.sequence IS '当前最大序列号';
+
+--
+-- Table structure for table im_group_join_request
+--
+
+DROP TABLE IF EXISTS im_group_join_request;
+CREATE TABLE im_group_join_request (
+  request_id varchar(50) NOT NULL,
+  group_id varchar(50) NOT NULL,
+  from_id varchar(50) NOT NULL,
+  message varchar(255) DEFAULT NULL,
+  approve_status integer NOT NULL DEFAULT 0,
+  handler_id varchar(50) DEFAULT NULL,
+  create_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  update_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  del_flag smallint NOT NULL DEFAULT 1,
+  version bigint DEFAULT NULL,
+  PRIMARY KEY (request_id)
+);
+
+-- 创建索引
+CREATE INDEX idx_group_join_request_group_status ON im_group_join_request (group_id, approve_status);
+CREATE INDEX idx_group_join_request_from_id ON im_group_join_request (from_id);
+
+-- 添加表注释
+COMMENT ON TABLE im_group_join_request IS '加群申请表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_group_join_request.request_id IS '申请ID';
+COMMENT ON COLUMN im_group_join_request.group_id IS '群组ID';
+COMMENT ON COLUMN im_group_join_request.from_id IS '申请人用户ID';
+COMMENT ON COLUMN im_group_join_request.message IS '申请附言';
+COMMENT ON COLUMN im_group_join_request.approve_status IS '审批状态（0待处理，1同意，2拒绝）';
+COMMENT ON COLUMN im_group_join_request.handler_id IS '处理人用户ID';
+COMMENT ON COLUMN im_group_join_request.create_time IS '创建时间';
+COMMENT ON COLUMN im_group_join_request.update_time IS '更新时间';
+COMMENT ON COLUMN im_group_join_request.del_flag IS '删除标识（1正常，0删除）';
+COMMENT ON COLUMN im_group_join_request.version IS '版本信息';
+
+--
+-- Table structure for table im_group_invite
+--
+
+DROP TABLE IF EXISTS im_group_invite;
+CREATE TABLE im_group_invite (
+  invite_token varchar(50) NOT NULL,
+  group_id varchar(50) NOT NULL,
+  creator_id varchar(50) NOT NULL,
+  expire_time timestamptz DEFAULT NULL,
+  max_uses integer DEFAULT NULL,
+  use_count integer NOT NULL DEFAULT 0,
+  require_approval smallint NOT NULL DEFAULT 0,
+  revoked smallint NOT NULL DEFAULT 0,
+  create_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  update_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (invite_token)
+);
+
+-- 创建索引
+CREATE INDEX idx_group_invite_group_id ON im_group_invite (group_id);
+
+-- 添加表注释
+COMMENT ON TABLE im_group_invite IS '群邀请链接表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_group_invite.invite_token IS '邀请令牌';
+COMMENT ON COLUMN im_group_invite.group_id IS '群组ID';
+COMMENT ON COLUMN im_group_invite.creator_id IS '创建人用户ID';
+COMMENT ON COLUMN im_group_invite.expire_time IS '过期时间（为空表示永不过期）';
+COMMENT ON COLUMN im_group_invite.max_uses IS '最大使用次数（为空表示不限）';
+COMMENT ON COLUMN im_group_invite.use_count IS '已使用次数';
+COMMENT ON COLUMN im_group_invite.require_approval IS '是否需要审批（1需要，0不需要）';
+COMMENT ON COLUMN im_group_invite.revoked IS '是否已撤销（1已撤销，0有效）';
+COMMENT ON COLUMN im_group_invite.create_time IS '创建时间';
+COMMENT ON COLUMN im_group_invite.update_time IS '更新时间';
+
+--
+-- Table structure for table im_group_message_sequence
+--
+
+DROP TABLE IF EXISTS im_group_message_sequence;
+CREATE TABLE im_group_message_sequence (
+  group_id varchar(255) NOT NULL,
+  sequence bigint NOT NULL DEFAULT 0,
+  PRIMARY KEY (group_id)
+);
+
+-- 添加表注释
+COMMENT ON TABLE im_group_message_sequence IS '群消息序列号表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_group_message_sequence.group_id IS '群组ID';
+COMMENT ON COLUMN im_group_message_sequence.sequence IS '当前最大序列号';
+
+--
+-- Table structure for table im_group_announcement
+--
+
+DROP TABLE IF EXISTS im_group_announcement;
+CREATE TABLE im_group_announcement (
+  announcement_id varchar(50) NOT NULL,
+  group_id varchar(50) NOT NULL,
+  author_id varchar(50) NOT NULL,
+  content text NOT NULL,
+  pinned smallint NOT NULL DEFAULT 0,
+  del_flag smallint NOT NULL DEFAULT 1,
+  create_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  update_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (announcement_id)
+);
+
+-- 创建索引
+CREATE INDEX idx_group_announcement_group_id ON im_group_announcement (group_id, create_time);
+
+-- 添加表注释
+COMMENT ON TABLE im_group_announcement IS '群公告表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_group_announcement.announcement_id IS '公告ID';
+COMMENT ON COLUMN im_group_announcement.group_id IS '群组ID';
+COMMENT ON COLUMN im_group_announcement.author_id IS '发布人用户ID';
+COMMENT ON COLUMN im_group_announcement.content IS '公告内容';
+COMMENT ON COLUMN im_group_announcement.pinned IS '是否置顶（1置顶，0不置顶）';
+COMMENT ON COLUMN im_group_announcement.del_flag IS '删除标识（1正常，0删除）';
+COMMENT ON COLUMN im_group_announcement.create_time IS '发布时间';
+COMMENT ON COLUMN im_group_announcement.update_time IS '更新时间';
+
+--
+-- Table structure for table im_group_announcement_ack
+--
+
+DROP TABLE IF EXISTS im_group_announcement_ack;
+CREATE TABLE im_group_announcement_ack (
+  announcement_id varchar(50) NOT NULL,
+  member_id varchar(50) NOT NULL,
+  ack_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (announcement_id, member_id)
+);
+
+-- 添加表注释
+COMMENT ON TABLE im_group_announcement_ack IS '群公告已读确认表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_group_announcement_ack.announcement_id IS '公告ID';
+COMMENT ON COLUMN im_group_announcement_ack.member_id IS '群成员用户ID';
+COMMENT ON COLUMN im_group_announcement_ack.ack_time IS '确认时间';
+
+--
+-- Table structure for table im_group_tag
+--
+
+DROP TABLE IF EXISTS im_group_tag;
+CREATE TABLE im_group_tag (
+  group_id varchar(50) NOT NULL,
+  tag varchar(20) NOT NULL,
+  create_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (group_id, tag)
+);
+
+-- 创建索引
+CREATE INDEX idx_group_tag_tag ON im_group_tag (tag);
+
+-- 添加表注释
+COMMENT ON TABLE im_group_tag IS '群标签表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_group_tag.group_id IS '群组ID';
+COMMENT ON COLUMN im_group_tag.tag IS '标签（小写）';
+COMMENT ON COLUMN im_group_tag.create_time IS '创建时间';
+
+--
+-- Table structure for table im_group_ban
+--
+
+DROP TABLE IF EXISTS im_group_ban;
+CREATE TABLE im_group_ban (
+  group_id varchar(50) NOT NULL,
+  member_id varchar(50) NOT NULL,
+  operator_id varchar(50) NOT NULL,
+  reason varchar(255) DEFAULT NULL,
+  expire_time timestamptz DEFAULT NULL,
+  create_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (group_id, member_id)
+);
+
+-- 添加表注释
+COMMENT ON TABLE im_group_ban IS '群黑名单表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_group_ban.group_id IS '群组ID';
+COMMENT ON COLUMN im_group_ban.member_id IS '被禁止入群的用户ID';
+COMMENT ON COLUMN im_group_ban.operator_id IS '操作人用户ID';
+COMMENT ON COLUMN im_group_ban.reason IS '原因';
+COMMENT ON COLUMN im_group_ban.expire_time IS '解除时间（为空表示永久）';
+COMMENT ON COLUMN im_group_ban.create_time IS '创建时间';
+
+--
+-- Table structure for table im_message_pin
+--
+
+DROP TABLE IF EXISTS im_message_pin;
+CREATE TABLE im_message_pin (
+  chat_id varchar(255) NOT NULL,
+  message_id varchar(255) NOT NULL,
+  pinned_by varchar(50) NOT NULL,
+  pin_time timestamptz DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (chat_id, message_id)
+);
+
+-- 添加表注释
+COMMENT ON TABLE im_message_pin IS '会话置顶消息表';
+
+-- 添加字段注释
+COMMENT ON COLUMN im_message_pin.chat_id IS '聊天ID（单聊为 single_ 开头，群聊为 group_ 开头）';
+COMMENT ON COLUMN im_message_pin.message_id IS '消息ID';
+COMMENT ON COLUMN im_message_pin.pinned_by IS '置顶操作人用户ID';
+COMMENT ON COLUMN im_message_pin.pin_time IS '置顶时间';
@@ -1,14 +1,19 @@
 use crate::{
-    config,
-    dto::{CreateUserReq, LoginReq, LoginResp},
-    models::SafeUser,
+    config::{self, AuthConfig},
+    dto::{
+        CreateUserReq, LoginReq, LoginResp, PasswordResetConfirmReq, PasswordResetReq,
+        TwoFactorLoginReq,
+    },
+    models::{SafeUser, User},
+    notifier,
     prelude::*,
-    service::{auth_service, user_service},
+    service::{auth_service, im_user_service, totp_service, user_service},
 };
 use salvo::{http::cookie::Cookie, oapi::extract::JsonBody, prelude::*};
 use tracing::error;
 
 /// 登录
+/// 开启两步验证的用户返回 challenge_token，需再调用 /auth/login/2fa 换取 token
 #[endpoint(tags("auth"))]
 pub async fn post_login(
     login_req: JsonBody<LoginReq>,
@@ -18,27 +23,61 @@ pub async fn post_login(
     let login_req = login_req.into_inner();
 
     let user = user_service::verify_user(&login_req.username, &login_req.password).await?;
+    im_user_service::ensure_not_forbidden(&user.open_id).await?;
 
-    let open_id = user.open_id.clone();
+    if totp_service::is_enabled(&user.open_id).await? {
+        let challenge =
+            auth_service::create_two_factor_challenge(&user.open_id, &config::get().auth).await?;
+        return json_ok(MyResponse::success_with_data(
+            "需要两步验证",
+            LoginResp {
+                token: None,
+                challenge_token: Some(challenge),
+            },
+        ));
+    }
+
+    let odata = issue_login_token(&user, res)?;
+    depot.inject(user);
+    json_ok(MyResponse::success_with_data("登录成功", odata))
+}
+
+/// 两步验证登录
+#[endpoint(tags("auth"))]
+pub async fn post_login_two_factor(
+    idata: JsonBody<TwoFactorLoginReq>,
+    res: &mut Response,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<LoginResp>> {
+    let idata = idata.into_inner();
 
-    depot.inject(user.clone());
+    let open_id = auth_service::consume_two_factor_challenge(
+        &idata.challenge_token,
+        &idata.code,
+        &config::get().auth,
+    )
+    .await?;
+    im_user_service::ensure_not_forbidden(&open_id).await?;
+    let user = user_service::get_by_open_id(&open_id).await?;
 
-    let open_id_number = open_id.parse::<u64>().map_err(|_| {
-        error!(user_id = %user.id, open_id = %open_id, "open_id 不是数字格式，无法生成 token");
+    let odata = issue_login_token(&user, res)?;
+    depot.inject(user);
+    json_ok(MyResponse::success_with_data("登录成功", odata))
+}
+
+fn issue_login_token(user: &User, res: &mut Response) -> AppResult<LoginResp> {
+    let open_id_number = user.open_id.parse::<u64>().map_err(|_| {
+        error!(user_id = %user.id, open_id = %user.open_id, "open_id 不是数字格式，无法生成 token");
         AppError::public("open id not exist")
     })?;
 
     let token = auth_service::get_token(open_id_number, &config::get().jwt)?;
+    set_token_cookie(res, &token);
 
-    let odata = LoginResp { token };
-
-    let cookie = Cookie::build(("jwt_token", odata.token.clone()))
-        .path("/")
-        .http_only(true)
-        .build();
-
-    res.add_cookie(cookie);
-    json_ok(MyResponse::success_with_data("登录成功", odata))
+    Ok(LoginResp {
+        token: Some(token),
+        challenge_token: None,
+    })
 }
 
 /// 注册
@@ -51,3 +90,79 @@ pub async fn register(idata: JsonBody<CreateUserReq>) -> JsonResult<MyResponse<S
         Err(err) => Err(err),
     }
 }
+
+pub(crate) fn set_token_cookie(res: &mut Response, token: &str) {
+    let cookie = Cookie::build(("jwt_token", token.to_string()))
+        .path("/")
+        .http_only(true)
+        .build();
+
+    res.add_cookie(cookie);
+}
+
+async fn find_user_by_account(account: &str) -> AppResult<User> {
+    if account.contains('@') {
+        user_service::get_by_email(account).await
+    } else {
+        user_service::get_by_name(account).await
+    }
+}
+
+/// 申请重置密码（发送验证码）
+#[endpoint(tags("auth"))]
+pub async fn request_password_reset(
+    idata: JsonBody<PasswordResetReq>,
+) -> JsonResult<MyResponse<()>> {
+    let idata = idata.into_inner();
+    let config = config::get();
+
+    // 发送失败只记录日志，不论账号是否存在都返回相同的响应，避免暴露账号是否存在
+    match find_user_by_account(&idata.account).await {
+        Ok(user) => {
+            if let Err(e) = send_reset_code(&user, &config.auth).await {
+                error!(user_id = %user.id, error = ?e, "发送密码重置验证码失败");
+            }
+        }
+        Err(AppError::NotFound(_)) => {
+            // 不暴露账号是否存在
+            warn!(account = %idata.account, "申请重置密码的账号不存在");
+        }
+        Err(e) => return Err(e),
+    }
+
+    json_ok(MyResponse::success_with_msg("如果账号存在，验证码已发送"))
+}
+
+/// 生成密码重置验证码并发送到用户邮箱
+async fn send_reset_code(user: &User, auth_config: &AuthConfig) -> AppResult<()> {
+    let code = auth_service::issue_reset_code(&user.open_id, auth_config).await?;
+    let body = f!(
+        "您的密码重置验证码为 {}，{} 分钟内有效。如非本人操作，请忽略。",
+        code,
+        auth_config.reset_code_ttl / 60
+    );
+    notifier::get()
+        .send(&user.email, "密码重置验证码", &body)
+        .await?;
+    Ok(())
+}
+
+/// 使用验证码重置密码
+#[endpoint(tags("auth"))]
+pub async fn reset_password(
+    idata: JsonBody<PasswordResetConfirmReq>,
+) -> JsonResult<MyResponse<()>> {
+    let idata = idata.into_inner();
+    let config = config::get();
+    user_service::validate_password(&idata.new_password)?;
+
+    let user = find_user_by_account(&idata.account)
+        .await
+        .map_err(|_| AppError::public("验证码无效或已过期"))?;
+
+    auth_service::consume_reset_code(&user.open_id, &idata.code, &config.auth).await?;
+    user_service::update_password(&user, &idata.new_password).await?;
+    auth_service::revoke_sessions(&user.open_id, &config.jwt).await?;
+
+    json_ok(MyResponse::success_with_msg("重置密码成功，请重新登录"))
+}
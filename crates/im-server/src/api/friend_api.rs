@@ -2,7 +2,7 @@ use crate::{
     models::SafeUser,
     models::User,
     prelude::*,
-    service::{friend_service, user_service},
+    service::{friend_service, im_user_service, user_service},
 };
 use salvo::{oapi::extract::PathParam, prelude::*};
 use tracing::info;
@@ -30,6 +30,7 @@ pub async fn add_friend(id: PathParam<String>, depot: &mut Depot) -> JsonResult<
             return Err(AppError::public("不能添加自己为好友"));
         }
 
+        im_user_service::ensure_can_add_friend(&current_user.open_id, &friend_id).await?;
         friend_service::add_friend(&current_user.open_id, &friend_id).await?;
         json_ok(MyResponse::success_with_msg("好友添加成功"))
     } else {
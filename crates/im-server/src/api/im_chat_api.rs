@@ -1,7 +1,10 @@
 use crate::{
-    models::{ChatWithName, ImChat, User},
+    api::im_group_api,
+    dto::MessagePinResp,
+    models::{ChatWithName, GroupSystemEvent, ImChat, ImMessagePin, User},
     prelude::*,
     service::im_chat_service,
+    service::im_message_pin_service::{self, PinChat},
 };
 use salvo::{
     oapi::extract::{JsonBody, PathParam},
@@ -183,3 +186,87 @@ pub async fn update_chat_remark(
         Err(AppError::unauthorized("用户未登录"))
     }
 }
+
+/// 获取会话的置顶消息
+#[endpoint(tags("im_chat"))]
+pub async fn get_chat_pins(
+    depot: &mut Depot,
+    chat_id: PathParam<String>,
+) -> JsonResult<MyResponse<Vec<MessagePinResp>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let chat_id = chat_id.into_inner();
+        let pins = im_message_pin_service::get_pins(&chat_id, &from_user.open_id).await?;
+        json_ok(MyResponse::success_with_data("Ok", pins))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 置顶消息（单聊双方都可以置顶，群聊只有群主和管理员可以置顶）
+#[endpoint(tags("im_chat"))]
+pub async fn pin_chat_message(
+    depot: &mut Depot,
+    chat_id: PathParam<String>,
+    message_id: PathParam<String>,
+) -> JsonResult<MyResponse<ImMessagePin>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let chat_id = chat_id.into_inner();
+        let message_id = message_id.into_inner();
+        let operator_id = from_user.open_id.clone();
+
+        let (pin, chat) =
+            im_message_pin_service::pin_message(&chat_id, &operator_id, &message_id).await?;
+        let event = GroupSystemEvent::MessagePinned {
+            chat_id,
+            operator_id: operator_id.clone(),
+            message_id,
+        };
+        send_pin_event(&chat, &operator_id, &event).await;
+        json_ok(MyResponse::success_with_data("Ok", pin))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 取消置顶消息
+#[endpoint(tags("im_chat"))]
+pub async fn unpin_chat_message(
+    depot: &mut Depot,
+    chat_id: PathParam<String>,
+    message_id: PathParam<String>,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let chat_id = chat_id.into_inner();
+        let message_id = message_id.into_inner();
+        let operator_id = from_user.open_id.clone();
+
+        let chat =
+            im_message_pin_service::unpin_message(&chat_id, &operator_id, &message_id).await?;
+        let event = GroupSystemEvent::MessageUnpinned {
+            chat_id,
+            operator_id: operator_id.clone(),
+            message_id,
+        };
+        send_pin_event(&chat, &operator_id, &event).await;
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 通知置顶变更：群聊保存为群系统消息并推送给所有成员，单聊推送给对方
+async fn send_pin_event(chat: &PinChat, operator_id: &str, event: &GroupSystemEvent) {
+    match chat {
+        PinChat::Group { group_id } => {
+            im_group_api::send_group_system_message(group_id, event).await;
+        }
+        PinChat::Single { peer_id } => match serde_json::to_value(event) {
+            Ok(payload) => {
+                im_group_api::push_group_notification(operator_id, peer_id, &payload).await;
+            }
+            Err(e) => {
+                warn!(error = ?e, "序列化置顶事件失败");
+            }
+        },
+    }
+}
@@ -1,13 +1,21 @@
 use std::sync::Arc;
 
 use crate::dto::{
-    AddFriendRequest, GetFriendsResp, GetFriendshipRequests, GetFriendshipResp,
-    HandleFriendshipRequests, SimpleFriendshipResp, UpdateRemarkReq,
+    AddFriendRequest, FriendCategoryReq, FriendCategoryResp, FriendRecommendationResp,
+    GetFriendsQuery, GetFriendsResp, GetFriendshipRequests, GetFriendshipResp,
+    GetRecommendationsQuery, HandleFriendshipRequests, MarkFriendshipRequestsReadReq,
+    SetFriendCategoriesReq, SimpleFriendshipResp, SyncFriendsQuery, SyncFriendsResp,
+    UpdateRemarkReq,
 };
-use crate::models::im_friendship::ImFriendshipRequest;
-use crate::models::{ChatMessage, User};
-use crate::service::{im_friendship_service, user_service};
-use crate::{db, mqtt, prelude::*, utils};
+use crate::models::im_friendship::{
+    APPROVE_STATUS_PENDING, APPROVE_STATUS_REJECTED, ImFriendshipRequest,
+};
+use crate::models::{ChatMessage, ImFriendCategory, User};
+use crate::service::im_friendship_service::FriendshipRequestOutcome;
+use crate::service::{
+    im_friend_category_service, im_friendship_service, im_user_service, user_service,
+};
+use crate::{config, db, mqtt, prelude::*, utils};
 use im_share::subscription::SubscriptionService;
 use salvo::oapi::extract::{JsonBody, PathParam, QueryParam};
 use salvo::prelude::*;
@@ -33,20 +41,36 @@ pub async fn get_friends_by_open_id(
 }
 
 /// 获取好友列表
+///
+/// 可通过 category_id 按好友分组过滤
 #[endpoint(tags("im_friendship"))]
-pub async fn get_friends(depot: &mut Depot) -> JsonResult<MyResponse<Vec<GetFriendsResp>>> {
+pub async fn get_friends(
+    query: QueryParam<GetFriendsQuery, false>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Vec<GetFriendsResp>>> {
     if let Ok(from_user) = depot.obtain::<User>() {
         let owner_id = from_user.open_id.clone();
+        let category_id = query.into_inner().and_then(|query| query.category_id);
         info!(
-            "获取好友列表: owner_id={}, name={}, open_id={:?}",
-            owner_id, from_user.name, from_user.open_id
+            "获取好友列表: owner_id={}, name={}, open_id={:?}, category_id={:?}",
+            owner_id, from_user.name, from_user.open_id, category_id
         );
 
+        let mut category_map =
+            im_friend_category_service::get_friend_category_map(&owner_id).await?;
+
         match im_friendship_service::get_friends(&owner_id).await {
             Ok(friends) => {
                 // 为每个好友查询用户信息
                 let mut friends_with_info = Vec::new();
                 for friend in friends {
+                    let category_ids = category_map.remove(&friend.to_id).unwrap_or_default();
+                    if let Some(category_id) = &category_id
+                        && !category_ids.contains(category_id)
+                    {
+                        continue;
+                    }
+
                     // 根据 to_id（可能是用户名、手机号或 open_id）查询用户信息
                     let friend_user = match user_service::get_by_name(&friend.to_id).await {
                         Ok(user) => Some(user),
@@ -56,9 +80,22 @@ pub async fn get_friends(depot: &mut Depot) -> JsonResult<MyResponse<Vec<GetFrie
                         },
                     };
 
+                    // 被对方拉黑时不展示对方资料
+                    let friend_user = match friend_user {
+                        Some(user)
+                            if im_friendship_service::is_blocked(&user.open_id, &owner_id)
+                                .await
+                                .unwrap_or(false) =>
+                        {
+                            None
+                        }
+                        other => other,
+                    };
+
                     let request_info = GetFriendsResp {
                         friendship: friend,
                         user: friend_user.map(|user| user.into()),
+                        category_ids,
                     };
                     friends_with_info.push(request_info);
                 }
@@ -71,6 +108,124 @@ pub async fn get_friends(depot: &mut Depot) -> JsonResult<MyResponse<Vec<GetFrie
     }
 }
 
+/// 增量同步好友列表
+///
+/// 返回序列号大于 since 的好友关系和好友分组变更
+#[endpoint(tags("im_friendship"))]
+pub async fn sync_friends(
+    query: QueryParam<SyncFriendsQuery, false>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<SyncFriendsResp>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let owner_id = from_user.open_id.clone();
+        let query = query.into_inner().unwrap_or_default();
+        let since = query.since.max(0);
+        let limit = query.limit.unwrap_or(500).clamp(1, 1000);
+
+        let mut friends = im_friendship_service::sync_friends(&owner_id, since, limit + 1).await?;
+        let has_more = friends.len() as i64 > limit;
+        friends.truncate(limit as usize);
+
+        let mut categories = im_friend_category_service::sync_categories(&owner_id, since).await?;
+        let mut sequence = friends
+            .last()
+            .and_then(|friend| friend.sequence)
+            .unwrap_or(since);
+        if has_more {
+            // 还有未同步的好友变更时，分组只返回到本次序列号为止
+            categories.retain(|category| category.sequence.is_some_and(|seq| seq <= sequence));
+        } else if let Some(seq) = categories
+            .iter()
+            .filter_map(|category| category.sequence)
+            .max()
+        {
+            sequence = sequence.max(seq);
+        }
+
+        let mut category_map =
+            im_friend_category_service::get_friend_category_map(&owner_id).await?;
+        let mut friends_with_info = Vec::with_capacity(friends.len());
+        for friend in friends {
+            let active = friend.del_flag != Some(0);
+            let user = if active {
+                match user_service::get_by_open_id(&friend.to_id).await.ok() {
+                    // 被对方拉黑时不展示对方资料
+                    Some(user)
+                        if im_friendship_service::is_blocked(&user.open_id, &owner_id)
+                            .await
+                            .unwrap_or(false) =>
+                    {
+                        None
+                    }
+                    other => other,
+                }
+            } else {
+                None
+            };
+            let category_ids = category_map.remove(&friend.to_id).unwrap_or_default();
+            friends_with_info.push(GetFriendsResp {
+                friendship: friend,
+                user: user.map(|user| user.into()),
+                category_ids,
+            });
+        }
+
+        json_ok(MyResponse::success_with_data(
+            "Ok",
+            SyncFriendsResp {
+                sequence,
+                has_more,
+                friends: friends_with_info,
+                categories,
+            },
+        ))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 好友推荐
+///
+/// 按共同好友数和共同群组数排序
+#[endpoint(tags("im_friendship"))]
+pub async fn get_recommendations(
+    query: QueryParam<GetRecommendationsQuery, false>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Vec<FriendRecommendationResp>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let limit = query
+            .into_inner()
+            .and_then(|query| query.limit)
+            .unwrap_or(20)
+            .clamp(1, 100);
+
+        // 自己被禁止添加好友时不推荐
+        match im_user_service::get_user_data(&from_user.open_id).await {
+            Ok(data) if data.disable_add_friend == 1 => {
+                return json_ok(MyResponse::success_with_data("Ok", Vec::new()));
+            }
+            Ok(_) | Err(AppError::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        let recommendations =
+            im_friendship_service::get_recommendations(&from_user.open_id, limit).await?;
+        let mut results = Vec::with_capacity(recommendations.len());
+        for recommendation in recommendations {
+            let user = user_service::get_by_open_id(&recommendation.user_id)
+                .await
+                .ok();
+            results.push(FriendRecommendationResp {
+                recommendation,
+                user: user.map(|user| user.into()),
+            });
+        }
+        json_ok(MyResponse::success_with_data("Ok", results))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
 /// 添加好友
 #[endpoint(tags("im_friendship"))]
 pub async fn add_friend(
@@ -82,7 +237,6 @@ pub async fn add_friend(
             .obtain::<Arc<SubscriptionService>>()
             .map_err(|_| AppError::internal("SubscriptionService not found"))?;
         let conn = db::pool();
-        let publisher = mqtt::get_mqtt_publisher();
         let req = req.into_inner();
 
         let from_id = from_user.open_id.clone();
@@ -111,15 +265,19 @@ pub async fn add_friend(
             }
 
         // 检查是否已经有待处理的好友请求（双向检查）
-        // 注意：只检查待处理的请求（approve_status = 0），已拒绝的请求（approve_status = 2）可以重新发送
+        // 注意：只检查待处理的请求，已拒绝和已过期的请求可以重新发送
+        let expire_days = config::get().friendship.request_expire_days;
+        im_friendship_service::expire_pending_requests_between(&from_id, &to_id, expire_days)
+            .await?;
         // 1. 检查是否已经向对方发送过待处理的请求（from_id -> to_id）
         let existing_requests_to =
-            im_friendship_service::get_friendship_requests(&to_id, Some(0)).await;
+            im_friendship_service::get_friendship_requests(&to_id, Some(APPROVE_STATUS_PENDING))
+                .await;
         if let Ok(requests) = existing_requests_to {
-            // 只检查待处理的请求（approve_status = 0）
+            // 只检查待处理的请求
             if requests
                 .iter()
-                .any(|r| r.from_id == from_id && r.approve_status == Some(0))
+                .any(|r| r.from_id == from_id && r.approve_status == Some(APPROVE_STATUS_PENDING))
             {
                 return Err(AppError::public("已经发送过好友请求，等待对方处理"));
             }
@@ -127,12 +285,13 @@ pub async fn add_friend(
 
         // 2. 检查对方是否已经向自己发送过待处理的请求（to_id -> from_id），如果是，应该提示用户直接同意
         let existing_requests_from =
-            im_friendship_service::get_friendship_requests(&from_id, Some(0)).await;
+            im_friendship_service::get_friendship_requests(&from_id, Some(APPROVE_STATUS_PENDING))
+                .await;
         if let Ok(requests) = existing_requests_from {
-            // 只检查待处理的请求（approve_status = 0）
+            // 只检查待处理的请求
             if requests
                 .iter()
-                .any(|r| r.from_id == to_id && r.approve_status == Some(0))
+                .any(|r| r.from_id == to_id && r.approve_status == Some(APPROVE_STATUS_PENDING))
             {
                 return Err(AppError::public(
                     "对方已经向您发送过好友请求，请先处理对方的请求",
@@ -141,9 +300,10 @@ pub async fn add_friend(
         }
 
         // 3. 如果之前有被拒绝的请求，允许重新发送（删除旧请求或创建新请求）
-        // 检查是否有被拒绝的请求（approve_status = 2）
+        // 检查是否有被拒绝的请求
         let _rejected_requests_to =
-            im_friendship_service::get_friendship_requests(&to_id, Some(2)).await;
+            im_friendship_service::get_friendship_requests(&to_id, Some(APPROVE_STATUS_REJECTED))
+                .await;
         // 如果有被拒绝的请求，可以重新发送（创建新请求会覆盖旧请求）
         // 这里不做任何处理，允许继续创建新请求
 
@@ -165,7 +325,7 @@ pub async fn add_friend(
             read_status: Some(0),
             add_source: req.add_source,
             message: req.message,
-            approve_status: Some(0), // 0: 待处理
+            approve_status: Some(APPROVE_STATUS_PENDING),
             create_time: Some(timestamp),
             update_time: Some(timestamp),
             sequence: Some(timestamp.unix_timestamp() * 1000),
@@ -173,109 +333,93 @@ pub async fn add_friend(
             version: Some(1),
         };
 
-        // 插入好友请求
-        match im_friendship_service::create_friendship_request(friendship_request).await {
-            Ok(_) => {
+        // 插入好友请求（对方无需验证时直接成为好友）
+        let outcome =
+            im_friendship_service::create_friendship_request(friendship_request, expire_days)
+                .await?;
+        let now_ms = OffsetDateTime::now_utc().unix_timestamp() * 1000;
+
+        match outcome {
+            FriendshipRequestOutcome::Pending => {
                 info!(
                     "创建好友请求成功: request_id={}, from_id={}, to_id={}",
                     request_id, from_id_clone, to_id_clone
                 );
 
                 // 通过 MQTT 推送好友请求通知给接收者
-                // to_id_clone 已经是用户名或手机号，直接查找
-                let to_user = match user_service::get_by_name(&to_id_clone).await {
-                    Ok(user) => Ok(user),
-                    Err(_) => user_service::get_by_phone(&to_id_clone).await,
+                let notification = serde_json::json!({
+                    "type": "friendship_request",
+                    "request_id": request_id,
+                    "from_id": from_id_clone,
+                    "to_id": to_id_clone,
+                    "remark": remark_clone,
+                    "message": message_clone,
+                    "add_source": add_source_clone,
+                });
+                let notification_message = ChatMessage {
+                    message_id: request_id.clone(),
+                    from_user_id: from_id_clone.clone(),
+                    to_user_id: to_id_clone.clone(),
+                    message: notification.to_string(),
+                    timestamp_ms: now_ms,
+                    file_url: None,
+                    file_name: None,
+                    file_type: None,
+                    chat_type: Some(1), // 1 = 单聊（好友请求也是单聊的一种）
                 };
+                push_friendship_notification(
+                    subscription_service,
+                    conn,
+                    &to_user,
+                    &notification_message,
+                )
+                .await;
 
-                if let Ok(to_user) = to_user {
-                    // 获取接收者的订阅ID
-                    let subscription_ids = {
-                        let mut ids = subscription_service.get_subscription_ids(to_user.id);
-                        // 如果内存中没有，从数据库查询（只查询最近24小时内创建的订阅，过滤掉已不在线的用户）
-                        if ids.is_empty()
-                            && let Ok(db_subscriptions) = sqlx::query_scalar!(
-                                r#"
-                                SELECT subscription_id FROM subscriptions
-                                 WHERE user_id = $1
-                                 AND created_at >= NOW() - INTERVAL '24 HOUR'
-                                 ORDER BY created_at DESC
-                                 "#,
-                                to_user.id
-                            )
-                            .fetch_all(conn)
-                            .await
-                            {
-                                for sub_id in &db_subscriptions {
-                                    subscription_service
-                                        .add_subscription_id(sub_id.clone(), to_user.id);
-                                }
-                                ids = subscription_service.get_subscription_ids(to_user.id);
-                            }
-                        ids
-                    };
+                json_ok(MyResponse::success_with_msg(format!(
+                    "好友请求已发送，等待对方同意: request_id={}",
+                    request_id
+                )))
+            }
+            FriendshipRequestOutcome::Accepted => {
+                info!(
+                    "对方无需验证，已直接添加好友: request_id={}, from_id={}, to_id={}",
+                    request_id, from_id_clone, to_id_clone
+                );
 
-                    // 构建好友请求通知消息
+                // 通知双方已成为好友
+                let notification = serde_json::json!({
+                    "type": "friendship_added",
+                    "request_id": request_id,
+                    "from_id": from_id_clone,
+                    "to_id": to_id_clone,
+                    "add_source": add_source_clone,
+                })
+                .to_string();
+                for (receiver, receiver_id) in
+                    [(&to_user, &to_id_clone), (from_user, &from_id_clone)]
+                {
                     let notification_message = ChatMessage {
-                        message_id: request_id.clone(),
+                        message_id: Ulid::new().to_string(),
                         from_user_id: from_id_clone.clone(),
-                        to_user_id: to_id_clone.clone(),
-                        message: format!(
-                            r#"{{"type":"friendship_request","request_id":"{}","from_id":"{}","to_id":"{}","remark":{},"message":{},"add_source":{}}}"#,
-                            request_id,
-                            from_id_clone,
-                            to_id_clone,
-                            remark_clone
-                                .as_ref()
-                                .map(|r| format!("\"{}\"", r))
-                                .unwrap_or_else(|| "null".to_string()),
-                            message_clone
-                                .as_ref()
-                                .map(|m| format!("\"{}\"", m))
-                                .unwrap_or_else(|| "null".to_string()),
-                            add_source_clone
-                                .as_ref()
-                                .map(|s| format!("\"{}\"", s))
-                                .unwrap_or_else(|| "null".to_string())
-                        ),
-                        timestamp_ms: OffsetDateTime::now_utc().unix_timestamp() * 1000,
+                        to_user_id: receiver_id.clone(),
+                        message: notification.clone(),
+                        timestamp_ms: now_ms,
                         file_url: None,
                         file_name: None,
                         file_type: None,
-                        chat_type: Some(1), // 1 = 单聊（好友请求也是单聊的一种）
+                        chat_type: Some(1),
                     };
-
-                    // 无论用户是否在线，都通过 MQTT 发布通知
-                    // MQTT broker 会自动处理离线消息（使用 QoS 1 和 clean_session=false）
-                    let to_mqtt_id = to_user.open_id.clone();
-                    let topic = utils::mqtt_user_topic(&to_mqtt_id);
-                    let is_online = !subscription_ids.is_empty();
-                    info!(to_id = %to_id_clone, is_online = is_online, %topic, "通过MQTT发布好友请求通知（broker会自动处理离线消息）");
-
-                    match utils::encode_message(&notification_message) {
-                        Ok(payload) => {
-                            if let Err(e) = publisher.publish(&topic, payload).await {
-                                error!(to_id = %to_id_clone, %topic, error = %e, "好友请求MQTT发布失败");
-                            } else {
-                                info!(to_id = %to_id_clone, %topic, is_online = is_online, "好友请求已通过MQTT发布（如果用户离线，broker会存储消息）");
-                            }
-                        }
-                        Err(e) => {
-                            error!(error = %e, "好友请求消息编码失败");
-                        }
-                    }
-                } else {
-                    warn!(to_id = %to_id_clone, "无法获取接收者信息，无法通过MQTT发送好友请求通知");
-                    // 无法获取用户信息时，无法通过 MQTT 发布，但好友请求已保存到数据库
-                    // 用户可以通过查询好友请求列表来获取
+                    push_friendship_notification(
+                        subscription_service,
+                        conn,
+                        receiver,
+                        &notification_message,
+                    )
+                    .await;
                 }
 
-                json_ok(MyResponse::success_with_msg(format!(
-                    "好友请求已发送，等待对方同意: request_id={}",
-                    request_id
-                )))
+                json_ok(MyResponse::success_with_msg("添加好友成功"))
             }
-            Err(err) => Err(err),
         }
     } else {
         Err(AppError::unauthorized("未登录"))
@@ -357,7 +501,7 @@ pub async fn update_remark(
     }
 }
 
-/// 切换拉黑状态
+/// 拉黑好友
 #[endpoint(tags("im_friendship"))]
 pub async fn black_friend(
     depot: &mut Depot,
@@ -374,16 +518,278 @@ pub async fn black_friend(
     }
 }
 
+/// 取消拉黑
+#[endpoint(tags("im_friendship"))]
+pub async fn unblock_friend(
+    depot: &mut Depot,
+    to_id: PathParam<String>,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let to_id = to_id.into_inner();
+        im_friendship_service::unblock_friend(&from_user.open_id, &to_id).await?;
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 获取黑名单
+#[endpoint(tags("im_friendship"))]
+pub async fn get_black_list(depot: &mut Depot) -> JsonResult<MyResponse<Vec<GetFriendsResp>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let black_list = im_friendship_service::get_black_list(&from_user.open_id).await?;
+        let mut category_map =
+            im_friend_category_service::get_friend_category_map(&from_user.open_id).await?;
+
+        let mut friends_with_info = Vec::with_capacity(black_list.len());
+        for friendship in black_list {
+            let user = user_service::get_by_open_id(&friendship.to_id).await.ok();
+            let category_ids = category_map.remove(&friendship.to_id).unwrap_or_default();
+            friends_with_info.push(GetFriendsResp {
+                friendship,
+                user: user.map(|user| user.into()),
+                category_ids,
+            });
+        }
+        json_ok(MyResponse::success_with_data("Ok", friends_with_info))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 获取好友分组列表
+#[endpoint(tags("im_friendship"))]
+pub async fn get_friend_categories(
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Vec<FriendCategoryResp>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let categories = im_friend_category_service::get_categories(&from_user.open_id).await?;
+        let category_map =
+            im_friend_category_service::get_friend_category_map(&from_user.open_id).await?;
+
+        let categories = categories
+            .into_iter()
+            .map(|category| {
+                let to_ids = category_map
+                    .iter()
+                    .filter(|(_, category_ids)| category_ids.contains(&category.category_id))
+                    .map(|(to_id, _)| to_id.clone())
+                    .collect();
+                FriendCategoryResp { category, to_ids }
+            })
+            .collect();
+        json_ok(MyResponse::success_with_data("Ok", categories))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 创建好友分组
+#[endpoint(tags("im_friendship"))]
+pub async fn create_friend_category(
+    depot: &mut Depot,
+    req: JsonBody<FriendCategoryReq>,
+) -> JsonResult<MyResponse<ImFriendCategory>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let req = req.into_inner();
+        let category =
+            im_friend_category_service::create_category(&from_user.open_id, &req.name).await?;
+        json_ok(MyResponse::success_with_data("Ok", category))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 重命名好友分组
+#[endpoint(tags("im_friendship"))]
+pub async fn update_friend_category(
+    depot: &mut Depot,
+    category_id: PathParam<String>,
+    req: JsonBody<FriendCategoryReq>,
+) -> JsonResult<MyResponse<ImFriendCategory>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let category_id = category_id.into_inner();
+        let req = req.into_inner();
+        let category = im_friend_category_service::rename_category(
+            &from_user.open_id,
+            &category_id,
+            &req.name,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_data("Ok", category))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 删除好友分组
+#[endpoint(tags("im_friendship"))]
+pub async fn delete_friend_category(
+    depot: &mut Depot,
+    category_id: PathParam<String>,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let category_id = category_id.into_inner();
+        im_friend_category_service::delete_category(&from_user.open_id, &category_id).await?;
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 设置好友所属分组
+#[endpoint(tags("im_friendship"))]
+pub async fn set_friend_categories(
+    depot: &mut Depot,
+    to_id: PathParam<String>,
+    req: JsonBody<SetFriendCategoriesReq>,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let to_id = to_id.into_inner();
+        let req = req.into_inner();
+        im_friend_category_service::set_friend_categories(
+            &from_user.open_id,
+            &to_id,
+            &req.category_ids,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
 /// 创建好友申请
+///
+/// 发送者固定为当前登录用户
 #[endpoint(tags("im_friendship"))]
 pub async fn create_friendship_request(
+    depot: &mut Depot,
     req: JsonBody<ImFriendshipRequest>,
 ) -> JsonResult<MyResponse<()>> {
-    let req = req.into_inner();
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let subscription_service = depot
+            .obtain::<Arc<SubscriptionService>>()
+            .map_err(|_| AppError::internal("SubscriptionService not found"))?;
+        let mut req = req.into_inner();
+        // 申请ID由服务端生成，避免客户端传入他人的申请ID
+        req.id = ulid::Ulid::new().to_string();
+        req.from_id = from_user.open_id.clone();
+        let request_id = req.id.clone();
+        let to_id = req.to_id.clone();
+        let payload = serde_json::json!({
+            "request_id": request_id,
+            "from_id": req.from_id,
+            "to_id": to_id,
+            "remark": req.remark,
+            "message": req.message,
+            "add_source": req.add_source,
+        });
+
+        let outcome = im_friendship_service::create_friendship_request(
+            req,
+            config::get().friendship.request_expire_days,
+        )
+        .await?;
+        let (kind, msg) = match outcome {
+            FriendshipRequestOutcome::Accepted => ("friendship_added", "添加好友成功"),
+            FriendshipRequestOutcome::Pending => ("friendship_request", "Ok"),
+        };
+        if let Ok(to_user) = user_service::get_by_open_id(&to_id).await {
+            notify_friendship_event(
+                subscription_service,
+                &from_user.open_id,
+                &to_user,
+                kind,
+                payload,
+            )
+            .await;
+        }
+        json_ok(MyResponse::success_with_msg(msg))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
 
-    match im_friendship_service::create_friendship_request(req).await {
-        Ok(_) => json_ok(MyResponse::success_with_msg("Ok")),
-        Err(err) => Err(err),
+/// 获取自己发出的好友申请
+#[endpoint(tags("im_friendship"))]
+pub async fn get_sent_friendship_requests(
+    query: QueryParam<GetFriendshipRequests, false>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Vec<GetFriendshipResp>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let approve_status = query
+            .into_inner()
+            .map(|approve_status| approve_status.approve_status);
+        let requests =
+            im_friendship_service::get_sent_friendship_requests(&from_user.open_id, approve_status)
+                .await?;
+
+        let mut requests_with_info = Vec::with_capacity(requests.len());
+        for request in requests {
+            let user = user_service::get_by_open_id(&request.to_id).await.ok();
+            requests_with_info.push(GetFriendshipResp {
+                friendship_req: request,
+                user: user.map(|user| user.into()),
+            });
+        }
+        json_ok(MyResponse::success_with_data("Ok", requests_with_info))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 撤回好友申请
+#[endpoint(tags("im_friendship"))]
+pub async fn withdraw_friendship_request(
+    depot: &mut Depot,
+    request_id: PathParam<String>,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let subscription_service = depot
+            .obtain::<Arc<SubscriptionService>>()
+            .map_err(|_| AppError::internal("SubscriptionService not found"))?;
+        let request_id = request_id.into_inner();
+        let request =
+            im_friendship_service::withdraw_friendship_request(&from_user.open_id, &request_id)
+                .await?;
+
+        if let Ok(to_user) = user_service::get_by_open_id(&request.to_id).await {
+            notify_friendship_event(
+                subscription_service,
+                &from_user.open_id,
+                &to_user,
+                "friendship_request_withdrawn",
+                serde_json::json!({
+                    "request_id": request.id,
+                    "from_id": request.from_id,
+                    "to_id": request.to_id,
+                }),
+            )
+            .await;
+        }
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 批量标记好友申请为已读
+#[endpoint(tags("im_friendship"))]
+pub async fn mark_friendship_requests_read(
+    depot: &mut Depot,
+    req: JsonBody<MarkFriendshipRequestsReadReq>,
+) -> JsonResult<MyResponse<u64>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let req = req.into_inner();
+        let count = im_friendship_service::mark_friendship_requests_read(
+            &from_user.open_id,
+            req.request_ids,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_data("Ok", count))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
     }
 }
 
@@ -469,13 +875,127 @@ pub async fn get_friendship_requests(
 /// 1：同意 2：拒绝
 #[endpoint(tags("im_friendship"))]
 pub async fn handle_friendship_request(
+    depot: &mut Depot,
     request_id: PathParam<String>,
     req: JsonBody<HandleFriendshipRequests>,
 ) -> JsonResult<MyResponse<()>> {
-    let request_id = request_id.into_inner();
-    let req = req.into_inner();
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let subscription_service = depot
+            .obtain::<Arc<SubscriptionService>>()
+            .map_err(|_| AppError::internal("SubscriptionService not found"))?;
+        let request_id = request_id.into_inner();
+        let req = req.into_inner();
+
+        let request = im_friendship_service::handle_friendship_request(
+            &from_user.open_id,
+            &request_id,
+            req.approve_status,
+            config::get().friendship.request_expire_days,
+        )
+        .await?;
+
+        // 通知申请发送者处理结果
+        if let Ok(sender) = user_service::get_by_open_id(&request.from_id).await {
+            notify_friendship_event(
+                subscription_service,
+                &from_user.open_id,
+                &sender,
+                "friendship_request_handled",
+                serde_json::json!({
+                    "request_id": request.id,
+                    "from_id": request.from_id,
+                    "to_id": request.to_id,
+                    "approve_status": request.approve_status,
+                }),
+            )
+            .await;
+        }
+
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
 
-    im_friendship_service::handle_friendship_request(&request_id, req.approve_status).await?;
+/// 构造并推送一条好友事件通知，payload 中会写入 type 字段
+async fn notify_friendship_event(
+    subscription_service: &SubscriptionService,
+    from_id: &str,
+    to_user: &User,
+    kind: &str,
+    mut payload: serde_json::Value,
+) {
+    payload["type"] = serde_json::Value::from(kind);
+    let notification_message = ChatMessage {
+        message_id: ulid::Ulid::new().to_string(),
+        from_user_id: from_id.to_string(),
+        to_user_id: to_user.open_id.clone(),
+        message: payload.to_string(),
+        timestamp_ms: OffsetDateTime::now_utc().unix_timestamp() * 1000,
+        file_url: None,
+        file_name: None,
+        file_type: None,
+        chat_type: Some(1),
+    };
+    push_friendship_notification(
+        subscription_service,
+        db::pool(),
+        to_user,
+        &notification_message,
+    )
+    .await;
+}
 
-    json_ok(MyResponse::success_with_msg("Ok"))
+/// 通过 MQTT 推送好友相关通知
+///
+/// 无论用户是否在线都发布，broker 会处理离线消息（QoS 1 + clean_session=false）
+async fn push_friendship_notification(
+    subscription_service: &SubscriptionService,
+    conn: &sqlx::PgPool,
+    to_user: &User,
+    notification_message: &ChatMessage,
+) {
+    // 获取接收者的订阅ID
+    let subscription_ids = {
+        let mut ids = subscription_service.get_subscription_ids(to_user.id);
+        // 如果内存中没有，从数据库查询（只查询最近24小时内创建的订阅，过滤掉已不在线的用户）
+        if ids.is_empty()
+            && let Ok(db_subscriptions) = sqlx::query_scalar!(
+                r#"
+                SELECT subscription_id FROM subscriptions
+                 WHERE user_id = $1
+                 AND created_at >= NOW() - INTERVAL '24 HOUR'
+                 ORDER BY created_at DESC
+                 "#,
+                to_user.id
+            )
+            .fetch_all(conn)
+            .await
+        {
+            for sub_id in &db_subscriptions {
+                subscription_service.add_subscription_id(sub_id.clone(), to_user.id);
+            }
+            ids = subscription_service.get_subscription_ids(to_user.id);
+        }
+        ids
+    };
+
+    let publisher = mqtt::get_mqtt_publisher();
+    let to_id = &to_user.open_id;
+    let topic = utils::mqtt_user_topic(to_id);
+    let is_online = !subscription_ids.is_empty();
+    info!(to_id = %to_id, is_online = is_online, %topic, "通过MQTT发布好友通知");
+
+    match utils::encode_message(notification_message) {
+        Ok(payload) => {
+            if let Err(e) = publisher.publish(&topic, payload).await {
+                error!(to_id = %to_id, %topic, error = %e, "好友通知MQTT发布失败");
+            } else {
+                info!(to_id = %to_id, %topic, is_online = is_online, "好友通知已通过MQTT发布");
+            }
+        }
+        Err(e) => {
+            error!(error = %e, "好友通知消息编码失败");
+        }
+    }
 }
@@ -1,22 +1,40 @@
+use crate::config;
 use crate::db;
 use crate::dto::{
-    AddGroupMemberRequest, CreateGroupRequest, UpdateGroupRequest, UpdateMemberAliasRequest,
-    UpdateMemberRoleRequest,
+    AddGroupMemberRequest, BanGroupMemberRequest, CreateGroupAnnouncementRequest,
+    CreateGroupInviteRequest, CreateGroupRequest, GetGroupJoinRequestsQuery,
+    GroupAnnouncementAckStatus, GroupAnnouncementResp, GroupJoinRequest, GroupMemberListResp,
+    GroupMemberQuery, GroupSearchQuery, GroupSearchResp, HandleGroupJoinRequest,
+    JoinByInviteRequest, SetGroupAnnouncementPinnedRequest, SetGroupMuteRequest,
+    SetGroupTagsRequest, SetMemberMuteRequest, TransferGroupOwnerRequest, UpdateGroupRequest,
+    UpdateMemberAliasRequest, UpdateMemberRoleRequest,
 };
 
-use crate::models::{ChatMessage, ImGroup, ImGroupMember, ImGroupMessage, User};
+use crate::fanout::{self, FanoutJob, FanoutPayload};
+use crate::models::{
+    ChatMessage, GROUP_SYSTEM_MESSAGE_TYPE, GroupMessageNotice, GroupRole, GroupSystemEvent,
+    ImGroup, ImGroupAnnouncement, ImGroupBan, ImGroupInvite, ImGroupJoinRequest, ImGroupMember,
+    ImGroupMessage, User,
+};
 
+use crate::models::im_group_join_request::JOIN_STATUS_APPROVED;
 use crate::prelude::*;
 
 use crate::service::im_friendship_service;
+use crate::service::im_group_announcement_service;
+use crate::service::im_group_avatar_service;
+use crate::service::im_group_ban_service;
+use crate::service::im_group_invite_service;
 use crate::service::im_group_service;
+use crate::service::im_group_service::GroupJoinOutcome;
 use crate::service::im_message_service;
 use crate::service::user_service;
 
 use crate::utils;
 use im_share::subscription::SubscriptionService;
+use salvo::fs::NamedFile;
 use salvo::oapi::endpoint;
-use salvo::oapi::extract::{JsonBody, PathParam};
+use salvo::oapi::extract::{JsonBody, PathParam, QueryParam};
 use salvo::prelude::*;
 use std::sync::Arc;
 use time::OffsetDateTime;
@@ -82,13 +100,22 @@ pub async fn get_group(group_id: PathParam<String>) -> JsonResult<MyResponse<ImG
     json_ok(MyResponse::success_with_data("Ok", group))
 }
 
-/// 获取群组成员列表
+/// 获取群组成员列表（分页，支持按角色、禁言状态筛选和按群昵称/用户名搜索）
 #[endpoint(tags("im_group"))]
 pub async fn get_group_members(
     group_id: PathParam<String>,
-) -> JsonResult<MyResponse<Vec<ImGroupMember>>> {
-    let members = im_group_service::get_group_members(&group_id).await?;
-    json_ok(MyResponse::success_with_data("Ok", members))
+    req: &mut Request,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<GroupMemberListResp>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let query: GroupMemberQuery = req.parse_queries()?;
+        let group_id = group_id.into_inner();
+        let resp =
+            im_group_service::query_group_members(&group_id, &from_user.open_id, query).await?;
+        json_ok(MyResponse::success_with_data("Ok", resp))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
 }
 
 /// 添加群组成员
@@ -190,7 +217,7 @@ pub async fn add_group_member(
                         owner_id: owner_id.clone(),
                         group_type: 1,                  // 私有群
                         group_name: "群聊".to_string(), // 默认名称，前端可以修改
-                        mute: Some(0),
+                        mute: Some(1),                  // 不禁言
                         apply_join_type: 1,
                         avatar: None,
                         max_member_count: None,
@@ -251,10 +278,33 @@ pub async fn add_group_member(
         };
 
         let role = req.role.unwrap_or(0);
+        let Some(target_role) = GroupRole::from_role(role).filter(|r| *r != GroupRole::Owner)
+        else {
+            return Err(AppError::public(f!("无效的角色值: {}", role)));
+        };
+
+        // 正式群组中，成员可以拉好友入群，直接设置为管理员需要群主权限
+        if let Ok(group) = im_group_service::get_group(&final_group_id).await {
+            let required = if target_role == GroupRole::Member {
+                GroupRole::Member
+            } else {
+                GroupRole::Owner
+            };
+            im_group_service::ensure_group_role(&group, &from_id, required).await?;
+        }
 
         im_group_service::add_group_member(&final_group_id, &to_id, role, req.alias).await?;
 
         info!("成功将用户 {} 添加到群组 {}", member_id, final_group_id);
+        send_group_system_message(
+            &final_group_id,
+            &GroupSystemEvent::GroupMemberJoined {
+                group_id: final_group_id.clone(),
+                member_id: to_id.clone(),
+                operator_id: Some(from_id.clone()),
+            },
+        )
+        .await;
 
         // 为新加入的成员创建聊天记录（如果还没有的话）
         // 这样即使没有发送过消息，群组也会出现在聊天列表中
@@ -285,6 +335,16 @@ pub async fn remove_group_member(
         );
 
         im_group_service::remove_group_member(&group_id, &member_id, &operator_id).await?;
+        let event = GroupSystemEvent::GroupMemberKicked {
+            group_id: group_id.clone(),
+            member_id: member_id.clone(),
+            operator_id: operator_id.clone(),
+        };
+        send_group_system_message(&group_id, &event).await;
+        // 被移除的成员已不在群内，单独通知
+        if let Ok(payload) = serde_json::to_value(&event) {
+            push_group_notification(&operator_id, &member_id, &payload).await;
+        }
 
         json_ok(MyResponse::success_with_msg("Ok"))
     } else {
@@ -315,16 +375,21 @@ pub async fn update_member_role(
             .await
         {
             Ok(_) => {
-                let role_name = match req.role {
-                    0 => "普通成员",
-                    1 => "管理员",
-                    2 => "群主",
-                    _ => "未知",
-                };
+                let role_name = GroupRole::from_role(req.role).map_or("未知", GroupRole::name);
                 info!(
                     "成功更新群成员角色: group_id={}, member_id={}, role={}",
                     group_id, member_id, role_name
                 );
+                send_group_system_message(
+                    &group_id,
+                    &GroupSystemEvent::GroupMemberRoleChanged {
+                        group_id: group_id.clone(),
+                        member_id: member_id.clone(),
+                        operator_id: operator_id.clone(),
+                        role: req.role,
+                    },
+                )
+                .await;
                 json_ok(MyResponse::success_with_msg(f!("已设置为{}", role_name)))
             }
             Err(e) => {
@@ -332,7 +397,7 @@ pub async fn update_member_role(
                     "更新群成员角色失败: group_id={}, member_id={}, role={}, operator_id={}, error={:?}",
                     group_id, member_id, req.role, operator_id, e
                 );
-                Err(AppError::internal("更新群成员角色失败"))
+                Err(e)
             }
         }
     } else {
@@ -438,12 +503,12 @@ pub async fn dissolve_group(
         info!("群组 {} 已解散", group_name);
 
         let message_id = Ulid::new().to_string();
-        let system_message = f!(
-            r#"{{"type":"group_dissolved","group_id":"{}","group_name":"{}","owner_id":"{}"}}"#,
-            group_id,
-            group_name,
-            owner_id
-        );
+        let system_message = serde_json::to_string(&GroupSystemEvent::GroupDissolved {
+            group_id: group_id.clone(),
+            group_name: group_name.clone(),
+            owner_id: owner_id.clone(),
+        })
+        .unwrap_or_default();
 
         // 保存系统消息到数据库
         let normalized_group_id = if group_id.starts_with("group_") {
@@ -458,7 +523,7 @@ pub async fn dissolve_group(
             from_id: "system".to_string(),
             message_body: system_message.clone(),
             message_time: now,
-            message_content_type: 100, // 系统消息类型
+            message_content_type: GROUP_SYSTEM_MESSAGE_TYPE,
             extra: None,
             del_flag: 1,
             sequence: Some(now.unix_timestamp() * 1000),
@@ -588,6 +653,28 @@ pub async fn update_group(
                     "成功更新群组信息: group_id={}, owner_id={}",
                     group_id, owner_id
                 );
+                if let Some(group_name) = req.group_name {
+                    send_group_system_message(
+                        &group_id,
+                        &GroupSystemEvent::GroupRenamed {
+                            group_id: group_id.clone(),
+                            operator_id: owner_id.clone(),
+                            group_name: group_name.trim().to_string(),
+                        },
+                    )
+                    .await;
+                }
+                if let Some(notification) = req.notification {
+                    send_group_system_message(
+                        &group_id,
+                        &GroupSystemEvent::GroupAnnouncementChanged {
+                            group_id: group_id.clone(),
+                            operator_id: owner_id.clone(),
+                            notification: notification.trim().to_string(),
+                        },
+                    )
+                    .await;
+                }
                 json_ok(MyResponse::success_with_msg("Ok"))
             }
             Err(e) => Err(e),
@@ -596,3 +683,805 @@ pub async fn update_group(
         Err(AppError::unauthorized("未登录"))
     }
 }
+
+/// 申请加入群组
+///
+/// 群组允许自由加入时直接入群，否则创建待审批的申请并通知群主和管理员
+#[endpoint(tags("im_group"))]
+pub async fn apply_join_group(
+    group_id: PathParam<String>,
+    req: JsonBody<GroupJoinRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Option<ImGroupJoinRequest>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let req = req.into_inner();
+        let user_id = from_user.open_id.clone();
+
+        match im_group_service::apply_join_group(&group_id, &user_id, req.message).await? {
+            GroupJoinOutcome::Joined => {
+                send_group_system_message(
+                    &group_id,
+                    &GroupSystemEvent::GroupMemberJoined {
+                        group_id: group_id.clone(),
+                        member_id: user_id,
+                        operator_id: None,
+                    },
+                )
+                .await;
+                json_ok(MyResponse::success_with_data("已加入群组", None))
+            }
+            GroupJoinOutcome::Pending(request) => {
+                let payload = serde_json::json!({
+                    "type": "group_join_request",
+                    "group_id": group_id,
+                    "request_id": request.request_id,
+                    "from_id": user_id,
+                    "message": request.message,
+                });
+                notify_group_managers(&group_id, &user_id, &payload).await;
+                json_ok(MyResponse::success_with_data(
+                    "申请已提交，等待审核",
+                    Some(request),
+                ))
+            }
+        }
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 获取加群申请列表
+///
+/// 只有群主和管理员可以查看
+#[endpoint(tags("im_group"))]
+pub async fn get_group_join_requests(
+    group_id: PathParam<String>,
+    query: QueryParam<GetGroupJoinRequestsQuery, false>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Vec<ImGroupJoinRequest>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let approve_status = query.into_inner().and_then(|query| query.approve_status);
+        let requests = im_group_service::get_group_join_requests(
+            &group_id,
+            &from_user.open_id,
+            approve_status,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_data("Ok", requests))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 处理加群申请
+///
+/// 1：同意 2：拒绝
+#[endpoint(tags("im_group"))]
+pub async fn handle_group_join_request(
+    group_id: PathParam<String>,
+    request_id: PathParam<String>,
+    req: JsonBody<HandleGroupJoinRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let request_id = request_id.into_inner();
+        let req = req.into_inner();
+        let operator_id = from_user.open_id.clone();
+
+        let request = im_group_service::handle_group_join_request(
+            &group_id,
+            &request_id,
+            &operator_id,
+            req.approve_status,
+        )
+        .await?;
+
+        // 通知申请人和其他管理员
+        let payload = serde_json::json!({
+            "type": "group_join_request_handled",
+            "group_id": group_id,
+            "request_id": request.request_id,
+            "from_id": request.from_id,
+            "handler_id": operator_id,
+            "approve_status": request.approve_status,
+        });
+        push_group_notification(&operator_id, &request.from_id, &payload).await;
+        notify_group_managers(&group_id, &operator_id, &payload).await;
+        if request.approve_status == JOIN_STATUS_APPROVED {
+            send_group_system_message(
+                &group_id,
+                &GroupSystemEvent::GroupMemberJoined {
+                    group_id: group_id.clone(),
+                    member_id: request.from_id.clone(),
+                    operator_id: Some(operator_id.clone()),
+                },
+            )
+            .await;
+        }
+
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 创建群邀请链接
+///
+/// 只有群主和管理员可以创建
+#[endpoint(tags("im_group"))]
+pub async fn create_group_invite(
+    group_id: PathParam<String>,
+    req: JsonBody<CreateGroupInviteRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<ImGroupInvite>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let req = req.into_inner();
+        let invite = im_group_invite_service::create_invite(
+            &group_id,
+            &from_user.open_id,
+            req.expire_seconds,
+            req.max_uses,
+            req.require_approval,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_data("Ok", invite))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 获取群邀请链接列表
+#[endpoint(tags("im_group"))]
+pub async fn get_group_invites(
+    group_id: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Vec<ImGroupInvite>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let invites = im_group_invite_service::get_invites(&group_id, &from_user.open_id).await?;
+        json_ok(MyResponse::success_with_data("Ok", invites))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 撤销群邀请链接
+#[endpoint(tags("im_group"))]
+pub async fn revoke_group_invite(
+    group_id: PathParam<String>,
+    invite_token: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let invite_token = invite_token.into_inner();
+        im_group_invite_service::revoke_invite(&group_id, &invite_token, &from_user.open_id)
+            .await?;
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 搜索公开群
+#[endpoint(tags("im_group"))]
+pub async fn search_groups(req: &mut Request) -> JsonResult<MyResponse<GroupSearchResp>> {
+    let query: GroupSearchQuery = req.parse_queries()?;
+    let resp = im_group_service::search_public_groups(
+        query.q,
+        query.tag,
+        query.current_page,
+        query.page_size,
+    )
+    .await?;
+    json_ok(MyResponse::success_with_data("Ok", resp))
+}
+
+/// 获取群标签
+#[endpoint(tags("im_group"))]
+pub async fn get_group_tags(group_id: PathParam<String>) -> JsonResult<MyResponse<Vec<String>>> {
+    let group_id = group_id.into_inner();
+    let tags = im_group_service::get_group_tags(&group_id).await?;
+    json_ok(MyResponse::success_with_data("Ok", tags))
+}
+
+/// 获取群组合头像（没有自定义头像的群组，由成员头像拼接生成）
+#[endpoint(tags("im_group"))]
+pub async fn get_group_avatar(
+    group_id: PathParam<String>,
+    req: &mut Request,
+    resp: &mut Response,
+    depot: &mut Depot,
+) -> AppResult<()> {
+    if depot.obtain::<User>().is_ok() {
+        let group_id = group_id.into_inner();
+        if group_id.contains("..") || group_id.contains('/') || group_id.contains('\\') {
+            return Err(AppError::public("无效的群组ID"));
+        }
+        let file_path = im_group_avatar_service::generated_avatar_path(&group_id);
+        if !file_path.exists() {
+            return Err(AppError::not_found("群头像不存在"));
+        }
+
+        // 地址中带有版本参数，成员变更后地址会变化，可以长期缓存
+        resp.add_header("Cache-Control", "public, max-age=5184000", true)
+            .unwrap();
+        match NamedFile::builder(file_path).build().await {
+            Ok(file) => file.send(req.headers(), resp).await,
+            Err(_) => resp.render(StatusError::internal_server_error()),
+        }
+        Ok(())
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 设置群标签（只有群主可以设置）
+#[endpoint(tags("im_group"))]
+pub async fn set_group_tags(
+    group_id: PathParam<String>,
+    req: JsonBody<SetGroupTagsRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Vec<String>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let tags =
+            im_group_service::set_group_tags(&group_id, &from_user.open_id, &req.into_inner().tags)
+                .await?;
+        json_ok(MyResponse::success_with_data("Ok", tags))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 发布群公告
+///
+/// 只有群主和管理员可以发布，发布后以系统消息推送给群成员
+#[endpoint(tags("im_group"))]
+pub async fn create_group_announcement(
+    group_id: PathParam<String>,
+    req: JsonBody<CreateGroupAnnouncementRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<ImGroupAnnouncement>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let req = req.into_inner();
+        let announcement = im_group_announcement_service::create_announcement(
+            &group_id,
+            &from_user.open_id,
+            &req.content,
+            req.pinned,
+        )
+        .await?;
+        send_group_system_message(
+            &group_id,
+            &GroupSystemEvent::GroupAnnouncementPublished {
+                group_id: group_id.clone(),
+                operator_id: from_user.open_id.clone(),
+                announcement_id: announcement.announcement_id.clone(),
+                content: announcement.content.clone(),
+                pinned: announcement.pinned == 1,
+            },
+        )
+        .await;
+        json_ok(MyResponse::success_with_data("Ok", announcement))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 获取群公告列表
+#[endpoint(tags("im_group"))]
+pub async fn get_group_announcements(
+    group_id: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Vec<GroupAnnouncementResp>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let announcements =
+            im_group_announcement_service::get_announcements(&group_id, &from_user.open_id).await?;
+        json_ok(MyResponse::success_with_data("Ok", announcements))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 设置群公告置顶
+#[endpoint(tags("im_group"))]
+pub async fn set_group_announcement_pinned(
+    group_id: PathParam<String>,
+    announcement_id: PathParam<String>,
+    req: JsonBody<SetGroupAnnouncementPinnedRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let announcement_id = announcement_id.into_inner();
+        im_group_announcement_service::set_announcement_pinned(
+            &group_id,
+            &announcement_id,
+            &from_user.open_id,
+            req.into_inner().pinned,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 删除群公告
+#[endpoint(tags("im_group"))]
+pub async fn delete_group_announcement(
+    group_id: PathParam<String>,
+    announcement_id: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let announcement_id = announcement_id.into_inner();
+        im_group_announcement_service::delete_announcement(
+            &group_id,
+            &announcement_id,
+            &from_user.open_id,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 确认已读群公告
+#[endpoint(tags("im_group"))]
+pub async fn acknowledge_group_announcement(
+    group_id: PathParam<String>,
+    announcement_id: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let announcement_id = announcement_id.into_inner();
+        im_group_announcement_service::acknowledge_announcement(
+            &group_id,
+            &announcement_id,
+            &from_user.open_id,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 获取群公告的确认情况（包括未确认的成员）
+#[endpoint(tags("im_group"))]
+pub async fn get_group_announcement_acks(
+    group_id: PathParam<String>,
+    announcement_id: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<GroupAnnouncementAckStatus>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let announcement_id = announcement_id.into_inner();
+        let status = im_group_announcement_service::get_announcement_ack_status(
+            &group_id,
+            &announcement_id,
+            &from_user.open_id,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_data("Ok", status))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 通过邀请链接加入群组
+#[endpoint(tags("im_group"))]
+pub async fn join_by_invite(
+    req: JsonBody<JoinByInviteRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Option<ImGroupJoinRequest>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let req = req.into_inner();
+        let user_id = from_user.open_id.clone();
+
+        let (invite, outcome) =
+            im_group_invite_service::join_by_invite(&req.invite_token, &user_id).await?;
+        match outcome {
+            GroupJoinOutcome::Joined => {
+                send_group_system_message(
+                    &invite.group_id,
+                    &GroupSystemEvent::GroupMemberJoined {
+                        group_id: invite.group_id.clone(),
+                        member_id: user_id,
+                        operator_id: Some(invite.creator_id.clone()),
+                    },
+                )
+                .await;
+                json_ok(MyResponse::success_with_data("已加入群组", None))
+            }
+            GroupJoinOutcome::Pending(request) => {
+                let payload = serde_json::json!({
+                    "type": "group_join_request",
+                    "group_id": invite.group_id,
+                    "request_id": request.request_id,
+                    "from_id": user_id,
+                    "message": request.message,
+                });
+                notify_group_managers(&invite.group_id, &user_id, &payload).await;
+                json_ok(MyResponse::success_with_data(
+                    "申请已提交，等待审核",
+                    Some(request),
+                ))
+            }
+        }
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 转让群主（只有群主可以转让）
+#[endpoint(tags("im_group"))]
+pub async fn transfer_group_owner(
+    group_id: PathParam<String>,
+    req: JsonBody<TransferGroupOwnerRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let req = req.into_inner();
+        let owner_id = from_user.open_id.clone();
+
+        im_group_service::transfer_ownership(&group_id, &owner_id, &req.new_owner_id).await?;
+
+        send_group_system_message(
+            &group_id,
+            &GroupSystemEvent::GroupOwnerTransferred {
+                group_id: group_id.clone(),
+                old_owner_id: owner_id,
+                new_owner_id: req.new_owner_id,
+            },
+        )
+        .await;
+        json_ok(MyResponse::success_with_msg("群主已转让"))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 退出群组（群主需先转让群主）
+#[endpoint(tags("im_group"))]
+pub async fn leave_group(
+    group_id: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let member_id = from_user.open_id.clone();
+
+        im_group_service::leave_group(&group_id, &member_id).await?;
+
+        send_group_system_message(
+            &group_id,
+            &GroupSystemEvent::GroupMemberLeft {
+                group_id: group_id.clone(),
+                member_id,
+            },
+        )
+        .await;
+        json_ok(MyResponse::success_with_msg("已退出群组"))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 设置全员禁言（群主和管理员不受限制）
+#[endpoint(tags("im_group"))]
+pub async fn set_group_mute(
+    group_id: PathParam<String>,
+    req: JsonBody<SetGroupMuteRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let req = req.into_inner();
+        let operator_id = from_user.open_id.clone();
+
+        im_group_service::set_group_mute(&group_id, &operator_id, req.mute).await?;
+
+        let event = if req.mute {
+            GroupSystemEvent::GroupMuted {
+                group_id: group_id.clone(),
+                operator_id,
+            }
+        } else {
+            GroupSystemEvent::GroupUnmuted {
+                group_id: group_id.clone(),
+                operator_id,
+            }
+        };
+        send_group_system_message(&group_id, &event).await;
+        json_ok(MyResponse::success_with_msg(if req.mute {
+            "已开启全员禁言"
+        } else {
+            "已关闭全员禁言"
+        }))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 设置群成员禁言
+#[endpoint(tags("im_group"))]
+pub async fn set_member_mute(
+    group_id: PathParam<String>,
+    member_id: PathParam<String>,
+    req: JsonBody<SetMemberMuteRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let member_id = member_id.into_inner();
+        let req = req.into_inner();
+        let operator_id = from_user.open_id.clone();
+
+        let mute_end_time = match req.mute_end_time {
+            Some(timestamp_ms) => Some(
+                OffsetDateTime::from_unix_timestamp(timestamp_ms / 1000)
+                    .map_err(|_| AppError::public("禁言截止时间无效"))?,
+            ),
+            None => None,
+        };
+        im_group_service::set_member_mute(
+            &group_id,
+            &operator_id,
+            &member_id,
+            req.mute,
+            mute_end_time,
+        )
+        .await?;
+
+        let event = if req.mute {
+            GroupSystemEvent::MemberMuted {
+                group_id: group_id.clone(),
+                operator_id,
+                member_id,
+                mute_end_time: req.mute_end_time,
+            }
+        } else {
+            GroupSystemEvent::MemberUnmuted {
+                group_id: group_id.clone(),
+                operator_id,
+                member_id,
+            }
+        };
+        send_group_system_message(&group_id, &event).await;
+        json_ok(MyResponse::success_with_msg(if req.mute {
+            "已禁言"
+        } else {
+            "已解除禁言"
+        }))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 获取群黑名单（只有群主和管理员可以查看）
+#[endpoint(tags("im_group"))]
+pub async fn get_group_bans(
+    group_id: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<Vec<ImGroupBan>>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let bans = im_group_ban_service::get_bans(&group_id, &from_user.open_id).await?;
+        json_ok(MyResponse::success_with_data("Ok", bans))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 将用户加入群黑名单，用户在群内时同时移出群组
+#[endpoint(tags("im_group"))]
+pub async fn ban_group_member(
+    group_id: PathParam<String>,
+    member_id: PathParam<String>,
+    req: JsonBody<BanGroupMemberRequest>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<ImGroupBan>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let member_id = member_id.into_inner();
+        let req = req.into_inner();
+        let operator_id = from_user.open_id.clone();
+
+        let expire_time = match req.expire_time {
+            Some(timestamp_ms) => Some(
+                OffsetDateTime::from_unix_timestamp(timestamp_ms / 1000)
+                    .map_err(|_| AppError::public("解除时间无效"))?,
+            ),
+            None => None,
+        };
+        let (ban, removed) = im_group_ban_service::ban_member(
+            &group_id,
+            &operator_id,
+            &member_id,
+            req.reason,
+            expire_time,
+        )
+        .await?;
+        info!(
+            "用户已加入群黑名单: group_id={}, member_id={}, operator_id={}, removed={}",
+            group_id, member_id, operator_id, removed
+        );
+
+        if removed {
+            let event = GroupSystemEvent::GroupMemberKicked {
+                group_id: group_id.clone(),
+                member_id: member_id.clone(),
+                operator_id: operator_id.clone(),
+            };
+            send_group_system_message(&group_id, &event).await;
+            // 被移除的成员已不在群内，单独通知
+            if let Ok(payload) = serde_json::to_value(&event) {
+                push_group_notification(&operator_id, &member_id, &payload).await;
+            }
+        }
+
+        json_ok(MyResponse::success_with_data("Ok", ban))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 将用户移出群黑名单
+#[endpoint(tags("im_group"))]
+pub async fn unban_group_member(
+    group_id: PathParam<String>,
+    member_id: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<()>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let member_id = member_id.into_inner();
+        im_group_ban_service::unban_member(&group_id, &from_user.open_id, &member_id).await?;
+        json_ok(MyResponse::success_with_msg("Ok"))
+    } else {
+        Err(AppError::unauthorized("未登录"))
+    }
+}
+
+/// 保存群系统事件消息并推送给所有群成员
+pub(crate) async fn send_group_system_message(group_id: &str, event: &GroupSystemEvent) {
+    let message_body = match serde_json::to_string(event) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(group_id = %group_id, error = ?e, "序列化群系统事件失败");
+            return;
+        }
+    };
+    let normalized_group_id = if group_id.starts_with("group_") {
+        group_id.to_string()
+    } else {
+        f!("group_{}", group_id)
+    };
+    let message_id = Ulid::new().to_string();
+    let now = OffsetDateTime::now_utc();
+    let group_message = ImGroupMessage {
+        message_id: message_id.clone(),
+        group_id: normalized_group_id.clone(),
+        from_id: "system".to_string(),
+        message_body: message_body.clone(),
+        message_time: now,
+        message_content_type: GROUP_SYSTEM_MESSAGE_TYPE,
+        extra: None,
+        del_flag: 1,
+        sequence: Some(now.unix_timestamp() * 1000),
+        message_random: Some(Ulid::new().to_string()),
+        create_time: now,
+        update_time: Some(now),
+        version: Some(1),
+        reply_to: None,
+    };
+    let timestamp_ms = now.unix_timestamp() * 1000;
+    let sequence = match im_message_service::save_group_message(group_message).await {
+        Ok(sequence) => sequence,
+        Err(e) => {
+            warn!(group_id = %group_id, error = ?e, "保存群系统消息失败");
+            timestamp_ms
+        }
+    };
+
+    // member_id 可能是用户名，按解析后的 open_id 推送
+    let mut recipients: Vec<String> =
+        match im_group_service::get_resolved_group_members(group_id).await {
+            Ok(members) => members.into_iter().map(|member| member.open_id).collect(),
+            Err(e) => {
+                warn!(group_id = %group_id, error = ?e, "获取群成员失败，群系统消息未推送");
+                return;
+            }
+        };
+    recipients.sort_unstable();
+    recipients.dedup();
+
+    // 与普通群消息一致：由后台扇出推送并写入离线消息，大群使用读扩散只推送新消息通知
+    let payload = if recipients.len() > config::get().fanout.read_diffusion_threshold {
+        FanoutPayload::Notice(GroupMessageNotice {
+            group_id: normalized_group_id.clone(),
+            sequence,
+            message_id,
+            timestamp_ms,
+        })
+    } else {
+        FanoutPayload::Message(ChatMessage {
+            message_id,
+            from_user_id: "system".to_string(),
+            to_user_id: normalized_group_id.clone(),
+            message: message_body,
+            timestamp_ms,
+            file_url: None,
+            file_name: None,
+            file_type: None,
+            chat_type: Some(2), // 群聊
+        })
+    };
+    if let Err(e) = fanout::submit(FanoutJob {
+        group_id: normalized_group_id,
+        payload,
+        recipients,
+    })
+    .await
+    {
+        warn!(group_id = %group_id, error = ?e, "提交群系统消息扇出任务失败");
+    }
+}
+
+/// 向群主和管理员推送群组通知（不包括操作者本人）
+async fn notify_group_managers(group_id: &str, from_id: &str, payload: &serde_json::Value) {
+    let manager_ids = im_group_service::get_group_manager_ids(group_id)
+        .await
+        .unwrap_or_default();
+    for manager_id in manager_ids {
+        if manager_id != from_id {
+            push_group_notification(from_id, &manager_id, payload).await;
+        }
+    }
+}
+
+/// 通过 MQTT 向单个用户推送群组相关通知
+pub(crate) async fn push_group_notification(
+    from_id: &str,
+    to_id: &str,
+    payload: &serde_json::Value,
+) {
+    let chat_message = ChatMessage {
+        message_id: Ulid::new().to_string(),
+        from_user_id: from_id.to_string(),
+        to_user_id: to_id.to_string(),
+        message: payload.to_string(),
+        timestamp_ms: OffsetDateTime::now_utc().unix_timestamp() * 1000,
+        file_url: None,
+        file_name: None,
+        file_type: None,
+        chat_type: Some(1),
+    };
+    let topic = utils::mqtt_user_topic(to_id);
+    match utils::encode_message(&chat_message) {
+        Ok(message) => {
+            if let Err(e) = crate::mqtt::get_mqtt_publisher()
+                .publish(&topic, message)
+                .await
+            {
+                warn!(to_id = %to_id, %topic, error = ?e, "推送群组通知失败");
+            }
+        }
+        Err(e) => {
+            warn!(to_id = %to_id, error = ?e, "编码群组通知失败");
+        }
+    }
+}
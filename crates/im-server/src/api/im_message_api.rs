@@ -4,12 +4,17 @@ use salvo::{
 };
 
 use crate::{
+    config::{self, BlockedPolicy},
     db,
     dto::ImGroupMessageStatus,
-    models::{ChatMessage, ImSingleMessage, User},
+    fanout::{self, FanoutJob, FanoutMetricsSnapshot, FanoutPayload},
+    models::{ChatMessage, GroupMessageNotice, ImGroupMessage, ImSingleMessage, User},
     mqtt,
     prelude::*,
-    service::{im_chat_service, im_group_service, im_message_service, user_service},
+    service::{
+        im_chat_service, im_friendship_service, im_group_service, im_message_service,
+        im_user_service, user_service,
+    },
     utils,
 };
 use im_share::redis::RedisClient;
@@ -40,7 +45,8 @@ pub async fn send_single_message(
     req: JsonBody<SendSingleMessageRequest>,
     depot: &mut Depot,
 ) -> JsonResult<MyResponse<()>> {
-    if let Ok(_user) = depot.obtain::<User>() {
+    if let Ok(user) = depot.obtain::<User>() {
+        im_user_service::ensure_not_silent(&user.open_id).await?;
         let req = req.into_inner();
         let conn = db::pool();
         let subscription_service = depot
@@ -90,6 +96,16 @@ pub async fn send_single_message(
         // 统一使用 open_id 作为消息的 from_id 和 to_id
         let from_open_id = from_user.open_id.clone();
         let to_open_id = to_user.open_id.clone();
+
+        // 被对方拉黑时按配置拒绝或静默丢弃
+        if im_friendship_service::is_blocked(&to_open_id, &from_open_id).await? {
+            info!(from_id = %from_open_id, to_id = %to_open_id, "发送者已被接收者拉黑");
+            return match config::get().message.blocked_policy {
+                BlockedPolicy::Reject => Err(AppError::public("消息已发出，但被对方拒收了")),
+                BlockedPolicy::Drop => json_ok(MyResponse::success_with_msg("Ok")),
+            };
+        }
+
         let now = OffsetDateTime::now_utc();
         let message_id = Ulid::new().to_string();
         let message = ImSingleMessage {
@@ -520,19 +536,12 @@ pub async fn send_group_message(
     depot: &mut Depot,
     req: JsonBody<SendGroupMessageRequest>,
 ) -> JsonResult<MyResponse<()>> {
-    if let Ok(_user) = depot.obtain::<User>() {
+    if let Ok(user) = depot.obtain::<User>() {
+        im_user_service::ensure_not_silent(&user.open_id).await?;
         let req = req.into_inner();
         let pool = db::pool();
-        let subscription_service = depot
-            .obtain::<Arc<SubscriptionService>>()
-            .map_err(|_| AppError::internal("SubscriptionService not found"))?;
-        let publisher = mqtt::get_mqtt_publisher();
 
         // 验证请求参数
-        if req.from_id.is_empty() {
-            return Err(AppError::public("from_id 不能为空"));
-        }
-
         if req.group_id.is_empty() {
             return Err(AppError::public("group_id 不能为空"));
         }
@@ -541,20 +550,8 @@ pub async fn send_group_message(
             return Err(AppError::public("消息内容不能为空"));
         }
 
-        // 先获取发送者的 open_id，确保统一使用 open_id
-        let from_user = match user_service::get_by_open_id(&req.from_id).await {
-            Ok(user) => user,
-            Err(_) => {
-                // 作为用户名查找
-                match user_service::get_by_name(&req.from_id).await {
-                    Ok(user) => user,
-                    Err(_) => {
-                        warn!(from_id = %req.from_id, "无法找到发送者用户");
-                        return Err(AppError::not_found("发送者用户不存在"));
-                    }
-                }
-            }
-        };
+        // 发送者固定为当前登录用户，忽略请求中的 from_id
+        let from_user = user;
 
         // 统一使用 open_id 作为消息的 from_id
         let from_open_id = from_user.open_id.clone();
@@ -592,6 +589,7 @@ pub async fn send_group_message(
                     );
                     return Err(AppError::public("群组已解散，无法发送消息"));
                 }
+                im_group_service::ensure_can_speak(&group, &from_open_id).await?;
             }
             Err(e) => {
                 // 如果群组不存在，可能是2人聊天，继续处理
@@ -607,8 +605,9 @@ pub async fn send_group_message(
             }
         }
 
-        // 先获取群组的所有成员
-        let members = match im_group_service::get_group_members(&normalized_group_id).await {
+        // 先获取群组的所有成员（已解析为用户，优先读取 Redis 缓存）
+        let members = match im_group_service::get_resolved_group_members(&normalized_group_id).await
+        {
             Ok(members) => {
                 info!(
                     original_group_id = %req.group_id,
@@ -697,28 +696,16 @@ pub async fn send_group_message(
             }
 
         // 根据 chat_type 决定保存到哪个表：chat_type=1保存到单聊表，chat_type=2保存到群聊表
+        let mut group_sequence = now_timestamp;
         if is_single_chat {
             // chat_type=1（单聊）：保存到单聊表
             // 找到接收者（除了发送者之外的成员）
-            let mut receiver_user_option = None;
-            for member in &members {
-                let member_user = match user_service::get_by_open_id(&member.member_id).await {
-                    Ok(user) => user,
-                    Err(_) => match user_service::get_by_name(&member.member_id).await {
-                        Ok(user) => user,
-                        Err(_) => continue,
-                    },
-                };
-                let member_open_id = member_user.open_id.clone();
-                let member_db_id = member_user.id;
-                if member_open_id != from_open_id && member_db_id != from_user.id {
-                    receiver_user_option = Some(member_user);
-                    break;
-                }
-            }
+            let receiver_option = members
+                .iter()
+                .find(|member| member.open_id != from_open_id && member.user_id != from_user.id);
 
-            if let Some(receiver_user) = receiver_user_option {
-                let receiver_open_id = receiver_user.open_id.clone();
+            if let Some(receiver) = receiver_option {
+                let receiver_open_id = receiver.open_id.clone();
 
                 // 保存到单聊表（双向保存：from->to 和 to->from）
                 let single_message = ImSingleMessage {
@@ -776,8 +763,9 @@ pub async fn send_group_message(
             };
 
             match im_message_service::save_group_message(group_message).await {
-                Ok(_) => {
-                    info!(group_id = %req.group_id, message_id = %message_id, chat_type = 2, "群聊消息已保存到群聊表");
+                Ok(sequence) => {
+                    group_sequence = sequence;
+                    info!(group_id = %req.group_id, message_id = %message_id, sequence, chat_type = 2, "群聊消息已保存到群聊表");
                 }
                 Err(e) => {
                     error!(group_id = %req.group_id, error = ?e, chat_type = 2, "保存群聊消息到群聊表失败");
@@ -786,8 +774,16 @@ pub async fn send_group_message(
             }
         }
 
-        // 消息保存成功后，继续处理推送和聊天记录更新
-        // 去重：使用 HashSet 确保每个 member_id 只处理一次
+        // 更新发送者的最后发言时间，失败不影响消息发送
+        if let Err(e) =
+            im_group_service::update_member_speak_date(&normalized_group_id, &from_open_id, now)
+                .await
+        {
+            warn!(group_id = %normalized_group_id, from_id = %from_open_id, error = ?e, "更新最后发言时间失败");
+        }
+
+        // 消息保存成功后，提交扇出任务并更新聊天记录
+        // 去重：使用 HashSet 确保每个成员只推送一次
         // 这样可以避免数据库中有重复记录时导致重复发送消息
         use std::collections::HashSet;
         let mut processed_member_ids = HashSet::new();
@@ -798,257 +794,71 @@ pub async fn send_group_message(
         let from_user_open_id = from_open_id.clone();
         let from_user_db_id = from_user.id;
 
-        // 为每个群成员（除了发送者）推送消息
+        // 收集接收者（除了发送者）
+        let mut recipients = Vec::with_capacity(members.len());
         for member in &members {
-            let member_id_str = &member.member_id;
-
-            // 获取成员用户信息（需要先获取才能比较）
-            let member_user = match user_service::get_by_open_id(member_id_str).await {
-                Ok(user) => user,
-                Err(_) => match user_service::get_by_name(member_id_str).await {
-                    Ok(user) => user,
-                    Err(_) => {
-                        warn!(member_id = %member_id_str, "无法找到群成员用户，跳过推送");
-                        continue;
-                    }
-                },
-            };
-
             // 跳过发送者自己：比较 open_id 或数据库ID
-            // 因为 member_id 可能是用户名、open_id 或 snowflake_id，需要统一比较
-            let member_open_id = member_user.open_id.clone();
-            let member_db_id = member_user.id;
-
-            if member_open_id == from_user_open_id || member_db_id == from_user_db_id {
+            if member.open_id == from_user_open_id || member.user_id == from_user_db_id {
                 skipped_sender_count += 1;
-                info!(group_id = %req.group_id, member_id = %member_id_str, member_open_id = %member_open_id, from_open_id = %from_user_open_id, "跳过发送者自己");
                 continue;
             }
 
             // 如果已经处理过这个成员，跳过（去重）
             // 使用 open_id 作为唯一标识，因为它是稳定的外部标识符
-            if !processed_member_ids.insert(member_open_id.clone()) {
+            if !processed_member_ids.insert(member.open_id.clone()) {
                 skipped_duplicate_count += 1;
-                warn!(group_id = %req.group_id, member_id = %member_id_str, member_open_id = %member_open_id, "检测到重复的群成员记录，跳过重复发送");
+                warn!(group_id = %req.group_id, member_id = %member.member_id, member_open_id = %member.open_id, "检测到重复的群成员记录，跳过重复发送");
                 continue;
             }
 
-            // 获取成员的MQTT ID
-            let member_mqtt_id = member_user.open_id.clone();
+            recipients.push(member.open_id.clone());
+        }
 
-            // 根据 chat_type 决定聊天类型和接收者ID（以 chat_type 为主，而不是成员数）
-            // chat_type=1（单聊），使用对方的 open_id 作为 to_user_id
-            // chat_type=2（群聊），使用 group_id 作为 to_user_id
-            let (chat_type_for_message, to_user_id) = if is_single_chat {
-                // 单聊：使用对方的 open_id
-                (Some(1), member_open_id.clone())
-            } else {
-                // 群聊：使用 normalized_group_id
-                (Some(2), normalized_group_id.clone())
-            };
+        // 根据 chat_type 决定聊天类型和接收者ID（以 chat_type 为主，而不是成员数）
+        // chat_type=1（单聊），使用对方的 open_id 作为 to_user_id
+        // chat_type=2（群聊），使用 group_id 作为 to_user_id
+        let (chat_type_for_message, to_user_id) = if is_single_chat {
+            (Some(1), recipients.first().cloned().unwrap_or_default())
+        } else {
+            (Some(2), normalized_group_id.clone())
+        };
+
+        // 构建消息格式
+        let chat_message = ChatMessage {
+            message_id: message_id.clone(),
+            from_user_id: from_user_open_id.clone(), // 使用 open_id
+            to_user_id,
+            message: req.message_body.clone(),
+            timestamp_ms: now_timestamp,
+            file_url,
+            file_name,
+            file_type,
+            chat_type: chat_type_for_message,
+        };
 
-            // 构建消息格式
-            let chat_message = ChatMessage {
+        // 推送（MQTT + Redis 离线消息）由后台扇出任务完成，接口在消息持久化后即返回
+        // 接收人数超过阈值的群使用读扩散：只推送新消息通知，成员按序列号从 im_group_message 拉取
+        let recipient_count = recipients.len();
+        let read_diffusion =
+            !is_single_chat && recipient_count > config::get().fanout.read_diffusion_threshold;
+        let payload = if read_diffusion {
+            FanoutPayload::Notice(GroupMessageNotice {
+                group_id: normalized_group_id.clone(),
+                sequence: group_sequence,
                 message_id: message_id.clone(),
-                from_user_id: from_user_open_id.clone(), // 使用 open_id
-                to_user_id: to_user_id.clone(),          // 单聊使用对方 open_id，群聊使用 group_id
-                message: req.message_body.clone(),
                 timestamp_ms: now_timestamp,
-                file_url: file_url.clone(),
-                file_name: file_name.clone(),
-                file_type: file_type.clone(),
-                chat_type: chat_type_for_message, // 根据 chat_type 决定：chat_type=1（单聊），chat_type=2（群聊）
-            };
-
-            // 从数据库查询订阅ID并同步到内存（如果内存中没有）
-            let subscription_ids = {
-                let mut ids = subscription_service.get_subscription_ids(member_user.id);
-                if ids.is_empty() {
-                    // 如果内存中没有，从数据库查询（只查询最近24小时内创建的订阅，过滤掉已不在线的用户）
-                    if let Ok(db_subscriptions) = sqlx::query_scalar!(
-                        r#"
-                        SELECT subscription_id FROM subscriptions
-                        WHERE user_id = $1
-                        AND created_at >= NOW() - INTERVAL '24 HOURS'
-                        ORDER BY created_at DESC
-                        "#,
-                        member_user.id
-                    )
-                    .fetch_all(pool)
-                    .await
-                    {
-                        for sub_id in &db_subscriptions {
-                            subscription_service
-                                .add_subscription_id(sub_id.clone(), member_user.id);
-                        }
-                        ids = subscription_service.get_subscription_ids(member_user.id);
-                    }
-                }
-                ids
-            };
-
-            // 通过 MQTT 发布消息给群成员
-            // 注意：broker 只有在客户端已经订阅过 topic 的情况下才会存储离线消息
-            // 如果用户从未连接过，broker 不会存储消息，但消息已保存到数据库，用户可以通过其他方式获取
-            let topic = format!("user/{}", member_mqtt_id);
-            let is_online = !subscription_ids.is_empty();
-            info!(group_id = %req.group_id, member_id = %member_id_str, is_online = is_online, subscription_count = subscription_ids.len(), %topic, "通过MQTT发布群消息（broker会自动处理离线消息，前提是用户曾经订阅过topic）");
-
-            // 添加调试日志，确认消息的 chat_type 是否正确设置
-            let chat_type_str = if is_single_chat { "单聊" } else { "群聊" };
-            info!(
-                group_id = %req.group_id,
-                member_id = %member_id_str,
-                member_count = member_count,
-                %topic,
-                message_id = %message_id,
-                chat_type = ?chat_message.chat_type,
-                from_user_id = %chat_message.from_user_id,
-                to_user_id = %chat_message.to_user_id,
-                "准备编码并发布MQTT消息（{}）",
-                chat_type_str
-            );
-
-            match serde_json::to_vec(&chat_message) {
-                Ok(payload) => {
-                    let payload_clone = payload.clone();
-                    // 尝试解析 payload 以确认 chat_type 是否被正确序列化
-                    if let Ok(decoded) = serde_json::from_slice::<serde_json::Value>(&payload) {
-                        info!(
-                            group_id = %req.group_id,
-                            member_id = %member_id_str,
-                            message_id = %message_id,
-                            chat_type_in_payload = ?decoded.get("chat_type"),
-                            "群组消息编码成功，chat_type 检查"
-                        );
-                    }
-
-                    // 混合方案：MQTT + Redis 离线消息
-                    // 1. MQTT 处理短期离线（用户曾经连接过，broker 会自动存储）
-                    // 2. Redis 处理长期离线或从未连接的用户（作为备份）
-                    // 先转换为 String，以便在多个地方使用
-                    let payload_str_result = String::from_utf8(payload);
-
-                    // 检查 payload 转换是否成功
-                    if let Err(e) = &payload_str_result {
-                        error!(
-                            group_id = %req.group_id,
-                            member_id = %member_id_str,
-                            member_open_id = %member_open_id,
-                            message_id = %message_id,
-                            error = %e,
-                            "⚠️ 群组消息 payload 转换为 String 失败，无法存储到 Redis"
-                        );
-                    }
-
-                    if let Err(e) = publisher.publish(&topic, payload_clone).await {
-                        error!(group_id = %req.group_id, member_id = %member_id_str, %topic, error = %e, message_id = %message_id, chat_type = ?chat_type, "消息MQTT发布失败，将消息存储到 Redis 作为备份");
-                        // MQTT 发布失败，存储到 Redis 作为备份
-                        match payload_str_result {
-                            Ok(payload_str) => {
-                                if let Err(redis_err) =
-                                    RedisClient::add_offline_message(&member_open_id, &payload_str)
-                                        .await
-                                {
-                                    warn!(
-                                        group_id = %req.group_id,
-                                        member_id = %member_id_str,
-                                        member_open_id = %member_open_id,
-                                        error = %redis_err,
-                                        "Redis 离线消息存储失败（消息已保存到数据库，不会丢失）"
-                                    );
-                                } else {
-                                    info!(
-                                        group_id = %req.group_id,
-                                        member_id = %member_id_str,
-                                        member_open_id = %member_open_id,
-                                        message_id = %message_id,
-                                        chat_type = ?chat_type,
-                                        "✅ 消息已存储到 Redis（MQTT 发布失败时的备份）"
-                                    );
-                                }
-                            }
-                            Err(e) => {
-                                error!(
-                                    group_id = %req.group_id,
-                                    member_id = %member_id_str,
-                                    member_open_id = %member_open_id,
-                                    message_id = %message_id,
-                                    error = %e,
-                                    "⚠️ 无法将消息存储到 Redis（payload 转换失败）"
-                                );
-                            }
-                        }
-                    } else {
-                        // MQTT 发布成功
-                        // 重要：无论用户是否在线，都存储到 Redis 作为备份
-                        // 原因：
-                        // 1. 如果用户在线但 WebSocket 连接不稳定，消息可能丢失
-                        // 2. 如果用户在消息发布后才连接，MQTT broker 不会存储消息（因为订阅发生在发布之后）
-                        // 3. Redis 作为统一备份，确保消息不丢失
-                        match payload_str_result {
-                            Ok(payload_str) => {
-                                info!(
-                                    group_id = %req.group_id,
-                                    member_id = %member_id_str,
-                                    member_open_id = %member_open_id,
-                                    message_id = %message_id,
-                                    chat_type = ?chat_type,
-                                    payload_length = payload_str.len(),
-                                    "准备存储群组消息到 Redis"
-                                );
-
-                                if let Err(redis_err) =
-                                    RedisClient::add_offline_message(&member_open_id, &payload_str)
-                                        .await
-                                {
-                                    warn!(
-                                        group_id = %req.group_id,
-                                        member_id = %member_id_str,
-                                        member_open_id = %member_open_id,
-                                        message_id = %message_id,
-                                        chat_type = ?chat_type,
-                                        error = %redis_err,
-                                        "❌ Redis 离线消息存储失败（MQTT 已发布，消息可能不会丢失）"
-                                    );
-                                } else if is_online {
-                                    info!(
-                                        group_id = %req.group_id,
-                                        member_id = %member_id_str,
-                                        member_open_id = %member_open_id,
-                                        %topic,
-                                        message_id = %message_id,
-                                        chat_type = ?chat_type,
-                                        "✅ 消息已保存到数据库，MQTT 发布成功，Redis 已备份（用户在线，三重保障）"
-                                    );
-                                } else {
-                                    info!(
-                                        group_id = %req.group_id,
-                                        member_id = %member_id_str,
-                                        member_open_id = %member_open_id,
-                                        message_id = %message_id,
-                                        chat_type = ?chat_type,
-                                        "✅ 消息已保存到数据库，MQTT broker 和 Redis 双重存储（用户离线，确保消息不丢失）"
-                                    );
-                                }
-                            }
-                            Err(e) => {
-                                error!(
-                                    group_id = %req.group_id,
-                                    member_id = %member_id_str,
-                                    member_open_id = %member_open_id,
-                                    message_id = %message_id,
-                                    error = %e,
-                                    "⚠️ 无法将消息存储到 Redis（payload 转换失败），MQTT 已发布"
-                                );
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    error!(member_id = %member_id_str, error = %e, "群消息编码失败");
-                }
-            }
+            })
+        } else {
+            FanoutPayload::Message(chat_message)
+        };
+        if let Err(e) = fanout::submit(FanoutJob {
+            group_id: normalized_group_id.clone(),
+            payload,
+            recipients,
+        })
+        .await
+        {
+            error!(group_id = %req.group_id, message_id = %message_id, error = ?e, "提交群消息扇出任务失败（消息已保存到数据库）");
         }
 
         info!(
@@ -1057,10 +867,11 @@ pub async fn send_group_message(
             total_members = members.len(),
             member_count = member_count,
             is_single_chat = is_single_chat,
-            processed_count = processed_member_ids.len(),
+            recipient_count = recipient_count,
+            read_diffusion = read_diffusion,
             skipped_sender = skipped_sender_count,
             skipped_duplicate = skipped_duplicate_count,
-            "消息发送完成（{}）",
+            "消息已提交推送（{}）",
             if is_single_chat { "单聊" } else { "群聊" }
         );
 
@@ -1070,25 +881,12 @@ pub async fn send_group_message(
         // 如果是单聊，需要为发送者和接收者都创建聊天记录
         if is_single_chat {
             // 找到接收者
-            let mut receiver_user_option = None;
-            for member in &members {
-                let member_user = match user_service::get_by_open_id(&member.member_id).await {
-                    Ok(user) => user,
-                    Err(_) => match user_service::get_by_name(&member.member_id).await {
-                        Ok(user) => user,
-                        Err(_) => continue,
-                    },
-                };
-                let member_open_id = member_user.open_id.clone();
-                let member_db_id = member_user.id;
-                if member_open_id != from_external_id && member_db_id != from_user_db_id {
-                    receiver_user_option = Some(member_user);
-                    break;
-                }
-            }
+            let receiver_option = members.iter().find(|member| {
+                member.open_id != from_external_id && member.user_id != from_user_db_id
+            });
 
-            if let Some(receiver_user) = receiver_user_option {
-                let receiver_external_id = receiver_user.open_id.clone();
+            if let Some(receiver) = receiver_option {
+                let receiver_external_id = receiver.open_id.clone();
 
                 // 生成统一的 chat_id（使用排序后的用户ID）
                 let (min_id, max_id) = if from_external_id < receiver_external_id {
@@ -1162,18 +960,7 @@ pub async fn send_group_message(
 
         // 为群成员（除了发送者）更新聊天记录（仅群聊）
         for member in &members {
-            let member_user = match user_service::get_by_open_id(&member.member_id).await {
-                Ok(user) => user,
-                Err(_) => match user_service::get_by_name(&member.member_id).await {
-                    Ok(user) => user,
-                    Err(_) => {
-                        warn!(member_id = %member.member_id, "无法找到群成员用户，跳过更新聊天记录");
-                        continue;
-                    }
-                },
-            };
-
-            let member_external_id = member_user.open_id.clone();
+            let member_external_id = member.open_id.clone();
 
             // 只处理群聊的聊天记录（单聊已经在上面处理了）
             if !is_single_chat {
@@ -1219,21 +1006,45 @@ pub async fn send_group_message(
     }
 }
 
-#[derive(Deserialize, ToSchema)]
+#[derive(Deserialize, ToSchema, Default)]
 pub struct GroupMessageParams {
+    /// 客户端本地已拉取到的序列号，返回序列号大于该值的消息
     pub since_sequence: Option<i64>,
-    pub limit: i32,
+    pub limit: Option<i32>,
 }
 
-/// 获取群聊信息
+/// 获取群聊信息（按序列号增量拉取，读扩散的大群收到新消息通知后调用）
 #[endpoint(tags("im_message"))]
 pub async fn get_group_message(
-    _depot: &mut Depot,
+    depot: &mut Depot,
     group_id: PathParam<String>,
-    _params: QueryParam<GroupMessageParams, true>,
-) -> JsonResult<()> {
-    let _req = group_id.into_inner();
-    todo!()
+    params: QueryParam<GroupMessageParams, false>,
+) -> JsonResult<MyResponse<Vec<ImGroupMessage>>> {
+    if let Ok(user) = depot.obtain::<User>() {
+        let group_id = group_id.into_inner();
+        let params = params.into_inner().unwrap_or_default();
+        let limit = params.limit.unwrap_or(100).clamp(1, 500);
+
+        let normalized_group_id = if group_id.starts_with("group_") {
+            group_id
+        } else {
+            format!("group_{}", group_id)
+        };
+        let members = im_group_service::get_resolved_group_members(&normalized_group_id).await?;
+        if !members.iter().any(|member| member.open_id == user.open_id) {
+            return Err(AppError::public("不是群成员"));
+        }
+
+        let messages = im_message_service::get_group_messages(
+            &normalized_group_id,
+            params.since_sequence,
+            limit,
+        )
+        .await?;
+        json_ok(MyResponse::success_with_data("Ok", messages))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
 }
 
 /// 标记群聊信息已读
@@ -1294,6 +1105,12 @@ pub async fn get_group_message_status(
     }
 }
 
+/// 管理员查看群消息扇出指标
+#[endpoint(tags("im_message"))]
+pub async fn get_fanout_metrics() -> JsonResult<MyResponse<FanoutMetricsSnapshot>> {
+    json_ok(MyResponse::success_with_data("Ok", fanout::snapshot()))
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 struct LimitParam(String);
 
@@ -3,8 +3,13 @@ use salvo::{
     prelude::*,
 };
 
-use crate::{dto::CreateImUserReq, dto::LoginReq, models::ImUserData};
-use crate::{dto::LoginResp, models::im_user::ImSafeUser, prelude::*, service::im_user_service};
+use crate::{dto::CreateImUserReq, dto::LoginReq, dto::UpdateUserFlagsReq, models::ImUserData};
+use crate::{
+    dto::LoginResp,
+    models::{User, im_user::ImSafeUser},
+    prelude::*,
+    service::{im_friendship_service, im_user_service},
+};
 
 /// 创建 im_user
 #[endpoint(tags("im_user"))]
@@ -33,7 +38,8 @@ pub async fn login(login_req: JsonBody<LoginReq>) -> JsonResult<MyResponse<Login
     json_ok(MyResponse::success_with_data(
         "登录成功",
         LoginResp {
-            token: "dummy_token".to_string(),
+            token: Some("dummy_token".to_string()),
+            challenge_token: None,
         },
     ))
 }
@@ -50,9 +56,19 @@ pub async fn get_user(user_id: PathParam<String>) -> JsonResult<MyResponse<ImSaf
 
 /// 获取用户数据
 #[endpoint(tags("im_user"))]
-pub async fn get_user_data(user_id: PathParam<String>) -> JsonResult<MyResponse<ImUserData>> {
+pub async fn get_user_data(
+    user_id: PathParam<String>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<ImUserData>> {
     let user_id = user_id.into_inner();
 
+    // 被对方拉黑时不可查看对方资料
+    if let Ok(from_user) = depot.obtain::<User>()
+        && im_friendship_service::is_blocked(&user_id, &from_user.open_id).await?
+    {
+        return Err(AppError::not_found(user_id));
+    }
+
     let user_data = im_user_service::get_user_data(&user_id).await?;
 
     json_ok(MyResponse::success_with_data("Ok", user_data))
@@ -61,9 +77,44 @@ pub async fn get_user_data(user_id: PathParam<String>) -> JsonResult<MyResponse<
 /// 更新用户数据
 #[endpoint(tags("im_user"))]
 pub async fn upsert_user_data(user_data: JsonBody<ImUserData>) -> JsonResult<MyResponse<()>> {
-    let user_data = user_data.into_inner();
+    let mut user_data = user_data.into_inner();
+
+    // 封禁、禁言、禁止添加好友只能通过管理员接口修改
+    match im_user_service::get_user_data(&user_data.user_id).await {
+        Ok(current) => {
+            user_data.forbidden_flag = current.forbidden_flag;
+            user_data.silent_flag = current.silent_flag;
+            user_data.disable_add_friend = current.disable_add_friend;
+        }
+        Err(AppError::NotFound(_)) => {
+            user_data.forbidden_flag = 0;
+            user_data.silent_flag = 0;
+            user_data.disable_add_friend = 0;
+        }
+        Err(e) => return Err(e),
+    }
 
     im_user_service::upsert_user_data(user_data).await?;
 
     json_ok(MyResponse::success_with_msg("Ok"))
 }
+
+/// 管理员更新用户状态标识（封禁 / 禁言 / 禁止添加好友）
+#[endpoint(tags("im_user"))]
+pub async fn update_user_flags(
+    user_id: PathParam<String>,
+    idata: JsonBody<UpdateUserFlagsReq>,
+) -> JsonResult<MyResponse<ImUserData>> {
+    let user_id = user_id.into_inner();
+    let idata = idata.into_inner();
+
+    let user_data = im_user_service::update_user_flags(
+        &user_id,
+        idata.forbidden_flag,
+        idata.silent_flag,
+        idata.disable_add_friend,
+    )
+    .await?;
+
+    json_ok(MyResponse::success_with_data("更新用户状态成功", user_data))
+}
@@ -1,14 +1,17 @@
 use std::sync::Arc;
 
 use crate::{
+    config::{self, BlockedPolicy},
     db,
     models::{
         ChatMessage, ImSingleMessage, User,
-        share::{SendRequest, Target, get_group_members},
+        share::{SendRequest, Target},
     },
     mqtt,
     prelude::*,
-    service::{im_message_service, user_service},
+    service::{
+        im_friendship_service, im_group_service, im_message_service, im_user_service, user_service,
+    },
     utils,
 };
 
@@ -33,6 +36,8 @@ pub async fn send_message(
     let conn = db::pool();
     let ts = OffsetDateTime::now_utc().unix_timestamp() * 1000;
 
+    im_user_service::ensure_not_silent(&req.from_user_id).await?;
+
     let mut recipient_user_ids: Vec<u64> = match &req.target {
         Target::User(uid_or_email) => {
             // 优先尝试作为 open_id 查询
@@ -64,11 +69,20 @@ pub async fn send_message(
             }
         }
         Target::Group(gid) => {
-            // 群组逻辑暂时简化，实际应该从数据库获取群组成员
-            get_group_members(gid)
+            let group = im_group_service::get_group(gid).await?;
+            im_group_service::ensure_can_speak(&group, &req.from_user_id).await?;
+            // member_id 可能是用户名，统一按解析后的 open_id 判断和扇出
+            let members = im_group_service::get_resolved_group_members(gid).await?;
+            if !members
+                .iter()
+                .any(|member| member.open_id == req.from_user_id)
+            {
+                return Err(AppError::public("不是群成员"));
+            }
+            members
                 .into_iter()
-                .filter_map(|uid| uid.parse().ok())
-                .filter(|id| id != &req.from_user_id.parse().unwrap_or(0))
+                .filter(|member| member.open_id != req.from_user_id)
+                .filter_map(|member| member.open_id.parse().ok())
                 .collect()
         }
     };
@@ -84,6 +98,14 @@ pub async fn send_message(
         // 注意：这里 to_user_mqtt_id 是 open_id 的数字形式（用于MQTT）
         // subscription_service 使用的是数据库 id，需要根据 open_id 查找数据库 id
         let open_id = to_user_mqtt_id.to_string();
+        if matches!(req.target, Target::User(_))
+            && im_friendship_service::is_blocked(&open_id, &req.from_user_id).await?
+        {
+            return match config::get().message.blocked_policy {
+                BlockedPolicy::Reject => Err(AppError::public("消息已发出，但被对方拒收了")),
+                BlockedPolicy::Drop => json_ok(MyResponse::success_with_msg("Ok")),
+            };
+        }
         let to_user = match user_service::get_by_open_id(&open_id).await {
             Ok(user) => user,
             Err(_) => {
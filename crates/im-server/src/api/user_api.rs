@@ -1,8 +1,13 @@
-use crate::dto::{CreateUserReq, UpdateUserReq, UserListQuery, UserListResp};
+use crate::api::auth_api::set_token_cookie;
+use crate::config;
+use crate::dto::{
+    ChangePasswordReq, CreateUserReq, LoginResp, TotpConfirmReq, TotpConfirmResp, TotpEnrollResp,
+    UpdateUserReq, UserListQuery, UserListResp,
+};
 use crate::models::SafeUser;
 use crate::models::User;
 use crate::prelude::*;
-use crate::service::user_service;
+use crate::service::{auth_service, im_friendship_service, totp_service, user_service};
 use salvo::oapi::extract::{JsonBody, PathParam};
 use salvo::prelude::*;
 
@@ -58,6 +63,84 @@ pub async fn update_current_user(
     }
 }
 
+/// 修改当前user密码
+/// 修改成功后其他会话全部失效，返回当前会话的新 token
+#[endpoint(tags("user"))]
+pub async fn change_password(
+    idata: JsonBody<ChangePasswordReq>,
+    res: &mut Response,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<LoginResp>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let idata = idata.into_inner();
+
+        let password_hash = from_user
+            .password_hash
+            .as_deref()
+            .ok_or_else(|| AppError::public("原密码错误"))?;
+        im_share::verify_password(&idata.old_password, password_hash)
+            .map_err(|_| AppError::public("原密码错误"))?;
+
+        user_service::update_password(from_user, &idata.new_password).await?;
+
+        let jwt_config = &config::get().jwt;
+        auth_service::revoke_sessions(&from_user.open_id, jwt_config).await?;
+
+        let open_id_number = from_user
+            .open_id
+            .parse::<u64>()
+            .map_err(|_| AppError::public("open id not exist"))?;
+        let token = auth_service::get_token(open_id_number, jwt_config)?;
+        set_token_cookie(res, &token);
+
+        json_ok(MyResponse::success_with_data(
+            "修改密码成功",
+            LoginResp {
+                token: Some(token),
+                challenge_token: None,
+            },
+        ))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 生成两步验证密钥
+#[endpoint(tags("user"))]
+pub async fn enroll_totp(depot: &mut Depot) -> JsonResult<MyResponse<TotpEnrollResp>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let (secret, otpauth_uri) =
+            totp_service::enroll(from_user, &config::get().auth.totp_issuer).await?;
+        json_ok(MyResponse::success_with_data(
+            "生成两步验证密钥成功",
+            TotpEnrollResp {
+                secret,
+                otpauth_uri,
+            },
+        ))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
+/// 确认开启两步验证，返回恢复码
+#[endpoint(tags("user"))]
+pub async fn confirm_totp(
+    idata: JsonBody<TotpConfirmReq>,
+    depot: &mut Depot,
+) -> JsonResult<MyResponse<TotpConfirmResp>> {
+    if let Ok(from_user) = depot.obtain::<User>() {
+        let idata = idata.into_inner();
+        let recovery_codes = totp_service::confirm(&from_user.open_id, &idata.code).await?;
+        json_ok(MyResponse::success_with_data(
+            "开启两步验证成功",
+            TotpConfirmResp { recovery_codes },
+        ))
+    } else {
+        Err(AppError::unauthorized("用户未登录"))
+    }
+}
+
 /// 获取user
 #[endpoint(tags("user"))]
 pub async fn get_user(
@@ -65,11 +148,13 @@ pub async fn get_user(
     depot: &mut Depot,
 ) -> JsonResult<MyResponse<SafeUser>> {
     let id = id.into_inner();
+    let mut viewer_open_id = None;
     if let Ok(from_user) = depot.obtain::<User>() {
         info!(
             "查询用户，open_id 或用户名: {} (请求来自用户ID: {})",
             id, from_user.name
         );
+        viewer_open_id = Some(from_user.open_id.clone());
     }
     if let Ok(numeric_id) = id.parse::<i64>() {
         let open_id = numeric_id.to_string();
@@ -81,6 +166,7 @@ pub async fn get_user(
                     name = user.name,
                     "通过 open_id 找到用户"
                 );
+                ensure_profile_visible(&user, viewer_open_id.as_deref(), &id).await?;
                 return json_ok(MyResponse::success_with_data(
                     "open_id获取用户成功",
                     user.into(),
@@ -107,6 +193,7 @@ pub async fn get_user(
                 name = user.name,
                 "通过 name 找到用户"
             );
+            ensure_profile_visible(&user, viewer_open_id.as_deref(), &id).await?;
             json_ok(MyResponse::success_with_data(
                 "name获取用户成功",
                 user.into(),
@@ -115,3 +202,17 @@ pub async fn get_user(
         Err(err) => Err(err),
     }
 }
+
+/// 被对方拉黑时不可查看对方资料
+async fn ensure_profile_visible(
+    user: &User,
+    viewer_open_id: Option<&str>,
+    id: &str,
+) -> AppResult<()> {
+    if let Some(viewer_open_id) = viewer_open_id
+        && im_friendship_service::is_blocked(&user.open_id, viewer_open_id).await?
+    {
+        return Err(AppError::not_found(id));
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthConfig {
+    /// 密码重置验证码有效期（秒）
+    #[serde(default = "default_reset_code_ttl")]
+    pub reset_code_ttl: u64,
+    /// 密码重置验证码最大尝试次数
+    #[serde(default = "default_reset_code_max_attempts")]
+    pub reset_code_max_attempts: i64,
+    /// 密码重置失败次数的统计窗口（秒），重新获取验证码不会重置，超过最大尝试次数后锁定到窗口结束
+    #[serde(default = "default_reset_lockout_ttl")]
+    pub reset_lockout_ttl: u64,
+    /// 认证器 App 中显示的签发方
+    #[serde(default = "default_totp_issuer")]
+    pub totp_issuer: String,
+    /// 两步验证登录挑战有效期（秒）
+    #[serde(default = "default_two_factor_challenge_ttl")]
+    pub two_factor_challenge_ttl: u64,
+    /// 两步验证登录挑战最大尝试次数
+    #[serde(default = "default_two_factor_max_attempts")]
+    pub two_factor_max_attempts: i64,
+    /// 两步验证按账号累计失败的窗口（秒），不随登录挑战重置
+    #[serde(default = "default_two_factor_lockout_ttl")]
+    pub two_factor_lockout_ttl: u64,
+    /// 窗口内两步验证最大失败次数，超过后锁定到窗口结束
+    #[serde(default = "default_two_factor_max_failures")]
+    pub two_factor_max_failures: i64,
+    /// 管理员 open_id 列表
+    #[serde(default)]
+    pub admin_open_ids: Vec<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            reset_code_ttl: default_reset_code_ttl(),
+            reset_code_max_attempts: default_reset_code_max_attempts(),
+            reset_lockout_ttl: default_reset_lockout_ttl(),
+            totp_issuer: default_totp_issuer(),
+            two_factor_challenge_ttl: default_two_factor_challenge_ttl(),
+            two_factor_max_attempts: default_two_factor_max_attempts(),
+            two_factor_lockout_ttl: default_two_factor_lockout_ttl(),
+            two_factor_max_failures: default_two_factor_max_failures(),
+            admin_open_ids: Vec::new(),
+        }
+    }
+}
+
+fn default_reset_code_ttl() -> u64 {
+    600
+}
+
+fn default_reset_code_max_attempts() -> i64 {
+    5
+}
+
+fn default_reset_lockout_ttl() -> u64 {
+    3600
+}
+
+fn default_totp_issuer() -> String {
+    "im-server".to_string()
+}
+
+fn default_two_factor_challenge_ttl() -> u64 {
+    300
+}
+
+fn default_two_factor_max_attempts() -> i64 {
+    5
+}
+
+fn default_two_factor_lockout_ttl() -> u64 {
+    900
+}
+
+fn default_two_factor_max_failures() -> i64 {
+    10
+}
@@ -0,0 +1,44 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FanoutConfig {
+    /// 扇出任务队列长度，队列满时发送请求等待
+    #[serde(default = "default_queue_size")]
+    pub queue_size: usize,
+    /// 同时执行的扇出任务数
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// 每批推送的成员数，每批的 Redis 写入合并为一次往返
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// 读扩散阈值：接收人数超过该值的群消息不再逐个写入离线消息，只推送新消息通知
+    #[serde(default = "default_read_diffusion_threshold")]
+    pub read_diffusion_threshold: usize,
+}
+
+impl Default for FanoutConfig {
+    fn default() -> Self {
+        Self {
+            queue_size: default_queue_size(),
+            concurrency: default_concurrency(),
+            batch_size: default_batch_size(),
+            read_diffusion_threshold: default_read_diffusion_threshold(),
+        }
+    }
+}
+
+fn default_queue_size() -> usize {
+    1024
+}
+
+fn default_concurrency() -> usize {
+    8
+}
+
+fn default_batch_size() -> usize {
+    200
+}
+
+fn default_read_diffusion_threshold() -> usize {
+    500
+}
@@ -0,0 +1,28 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FriendshipConfig {
+    /// 好友申请有效期（天），超时未处理的申请将被标记为已过期
+    #[serde(default = "default_request_expire_days")]
+    pub request_expire_days: i64,
+    /// 过期好友申请的检查间隔（秒）
+    #[serde(default = "default_request_expire_interval")]
+    pub request_expire_interval: u64,
+}
+
+impl Default for FriendshipConfig {
+    fn default() -> Self {
+        Self {
+            request_expire_days: default_request_expire_days(),
+            request_expire_interval: default_request_expire_interval(),
+        }
+    }
+}
+
+fn default_request_expire_days() -> i64 {
+    7
+}
+
+fn default_request_expire_interval() -> u64 {
+    3600
+}
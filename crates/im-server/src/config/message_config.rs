@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct MessageConfig {
+    /// 向拉黑自己的用户发送消息时的处理方式
+    #[serde(default)]
+    pub blocked_policy: BlockedPolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockedPolicy {
+    /// 拒绝发送并提示发送者
+    #[default]
+    Reject,
+    /// 静默丢弃，发送者感知不到
+    Drop,
+}
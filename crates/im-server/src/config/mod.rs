@@ -1,6 +1,11 @@
+mod auth_config;
 mod db_config;
+mod fanout_config;
+mod friendship_config;
 mod jwt_config;
 mod log_config;
+mod message_config;
+mod notifier_config;
 mod upload_config;
 
 use figment::Figment;
@@ -10,9 +15,14 @@ use im_share::redis::RedisConfig;
 use serde::Deserialize;
 use std::sync::OnceLock;
 
+pub use auth_config::AuthConfig;
 pub use db_config::DbConfig;
+pub use fanout_config::FanoutConfig;
+pub use friendship_config::FriendshipConfig;
 pub use jwt_config::JwtConfig;
 pub use log_config::LogConfig;
+pub use message_config::{BlockedPolicy, MessageConfig};
+pub use notifier_config::{NotifierConfig, SmtpConfig};
 pub use upload_config::UploadConfig;
 
 pub static CONFIG: OnceLock<ServerConfig> = OnceLock::new();
@@ -44,6 +54,16 @@ pub struct ServerConfig {
     pub redis: RedisConfig,
     pub upload: UploadConfig,
     pub mqtt: MqttConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub notifier: NotifierConfig,
+    #[serde(default)]
+    pub message: MessageConfig,
+    #[serde(default)]
+    pub friendship: FriendshipConfig,
+    #[serde(default)]
+    pub fanout: FanoutConfig,
 }
 
 pub fn default_true() -> bool {
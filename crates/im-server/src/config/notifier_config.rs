@@ -0,0 +1,47 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifierConfig {
+    /// 通知方式：smtp / file
+    #[serde(default = "default_notifier_kind")]
+    pub kind: String,
+    /// file 方式下的输出文件
+    #[serde(default = "default_notifier_file_path")]
+    pub file_path: String,
+    #[serde(default)]
+    pub smtp: Option<SmtpConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    pub from: String,
+}
+
+impl Default for NotifierConfig {
+    fn default() -> Self {
+        Self {
+            kind: default_notifier_kind(),
+            file_path: default_notifier_file_path(),
+            smtp: None,
+        }
+    }
+}
+
+fn default_notifier_kind() -> String {
+    "file".to_string()
+}
+
+fn default_notifier_file_path() -> String {
+    "logs/notifier.log".to_string()
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
@@ -1,8 +1,12 @@
 use salvo::oapi::ToSchema;
 
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
-use crate::models::{SafeUser, im_friendship::ImFriendship, im_friendship::ImFriendshipRequest};
+use crate::models::{
+    FriendRecommendation, ImFriendCategory, SafeUser, im_friendship::ImFriendship,
+    im_friendship::ImFriendshipRequest,
+};
 
 #[derive(Deserialize, ToSchema)]
 pub struct CreateUserReq {
@@ -63,7 +67,51 @@ pub struct LoginReq {
 
 #[derive(Serialize, ToSchema)]
 pub struct LoginResp {
-    pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// 开启两步验证时返回，用于换取 token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge_token: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TwoFactorLoginReq {
+    pub challenge_token: String,
+    pub code: String, // TOTP 验证码或恢复码
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TotpEnrollResp {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TotpConfirmReq {
+    pub code: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TotpConfirmResp {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ChangePasswordReq {
+    pub old_password: String,
+    pub new_password: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordResetReq {
+    pub account: String, // 用户名或邮箱
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordResetConfirmReq {
+    pub account: String,
+    pub code: String,
+    pub new_password: String,
 }
 
 #[derive(Debug, Clone, Deserialize, ToSchema)]
@@ -71,6 +119,12 @@ pub struct HandleFriendshipRequests {
     pub approve_status: i32,
 }
 
+#[derive(Debug, Clone, Deserialize, ToSchema, Default)]
+pub struct MarkFriendshipRequestsReadReq {
+    /// 要标记已读的请求ID，为空时标记全部
+    pub request_ids: Option<Vec<String>>,
+}
+
 #[derive(Debug, Clone, Deserialize, ToSchema, Default)]
 pub struct GetFriendshipRequests {
     pub approve_status: i32,
@@ -86,6 +140,62 @@ pub struct GetFriendshipResp {
 pub struct GetFriendsResp {
     pub friendship: ImFriendship,
     pub user: Option<SafeUser>,
+    /// 好友所属分组ID
+    pub category_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema, Default)]
+pub struct GetFriendsQuery {
+    /// 按好友分组过滤
+    pub category_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema, Default)]
+pub struct SyncFriendsQuery {
+    /// 客户端本地已同步到的序列号，首次同步传 0
+    #[serde(default)]
+    pub since: i64,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SyncFriendsResp {
+    /// 本次同步到的序列号，下次同步时作为 since 传入
+    pub sequence: i64,
+    /// 是否还有未同步的变更
+    pub has_more: bool,
+    /// 变更的好友关系（del_flag = 0 表示已删除）
+    pub friends: Vec<GetFriendsResp>,
+    /// 变更的好友分组（del_flag = 0 表示已删除）
+    pub categories: Vec<ImFriendCategory>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema, Default)]
+pub struct GetRecommendationsQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FriendRecommendationResp {
+    pub recommendation: FriendRecommendation,
+    pub user: Option<SafeUser>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct FriendCategoryReq {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetFriendCategoriesReq {
+    pub category_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FriendCategoryResp {
+    pub category: ImFriendCategory,
+    /// 分组内的好友ID
+    pub to_ids: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, ToSchema)]
@@ -158,6 +268,172 @@ pub struct UpdateMemberRoleRequest {
     pub role: i32, // 0=普通成员，1=管理员，2=群主
 }
 
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetGroupMuteRequest {
+    pub mute: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetMemberMuteRequest {
+    pub mute: bool,
+    /// 禁言截止时间（毫秒时间戳），为空表示永久禁言
+    pub mute_end_time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct BanGroupMemberRequest {
+    pub reason: Option<String>,
+    /// 解除时间（毫秒时间戳），为空表示永久
+    pub expire_time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct TransferGroupOwnerRequest {
+    pub new_owner_id: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GroupMemberQuery {
+    /// 按角色筛选：owner/admin/member
+    pub role: Option<String>,
+    /// 按禁言状态筛选
+    pub muted: Option<bool>,
+    /// 关键字，匹配群昵称和用户名
+    pub q: Option<String>,
+    #[serde(default = "default_page")]
+    pub current_page: i64,
+    #[serde(default = "default_page_size")]
+    pub page_size: i64,
+}
+
+/// 群成员列表项
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GroupMemberItem {
+    pub member_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_id: Option<String>,
+    /// 用户名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// 群昵称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    pub role: i32,
+    /// 当前是否处于禁言状态
+    pub muted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mute_end_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub join_time: Option<OffsetDateTime>,
+    /// 最后发言时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speak_date: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GroupMemberListResp {
+    pub members: Vec<GroupMemberItem>,
+    pub total: i64,
+    pub current_page: i64,
+    pub page_size: i64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GroupSearchQuery {
+    /// 关键字，匹配群名称、群简介和标签
+    pub q: Option<String>,
+    /// 按标签精确筛选
+    pub tag: Option<String>,
+    #[serde(default = "default_page")]
+    pub current_page: i64,
+    #[serde(default = "default_page_size")]
+    pub page_size: i64,
+}
+
+/// 公开群搜索结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GroupSearchItem {
+    pub group_id: String,
+    pub group_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub introduction: Option<String>,
+    pub apply_join_type: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_member_count: Option<i32>,
+    pub member_count: i64,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GroupSearchResp {
+    pub groups: Vec<GroupSearchItem>,
+    pub total: i64,
+    pub current_page: i64,
+    pub page_size: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetGroupTagsRequest {
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateGroupAnnouncementRequest {
+    pub content: String,
+    /// 是否置顶
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetGroupAnnouncementPinnedRequest {
+    pub pinned: bool,
+}
+
+/// 群公告列表项
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GroupAnnouncementResp {
+    pub announcement_id: String,
+    pub group_id: String,
+    pub author_id: String,
+    pub content: String,
+    pub pinned: i16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_time: Option<OffsetDateTime>,
+    /// 当前用户是否已确认
+    pub acknowledged: bool,
+    /// 已确认人数
+    pub ack_count: i64,
+}
+
+/// 群公告的确认情况
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GroupAnnouncementAckStatus {
+    pub announcement_id: String,
+    /// 已确认人数
+    pub ack_count: i64,
+    /// 尚未确认的群成员
+    pub unacknowledged_member_ids: Vec<String>,
+}
+
+/// 会话置顶消息，附带消息内容
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MessagePinResp {
+    pub chat_id: String,
+    pub message_id: String,
+    pub pinned_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pin_time: Option<OffsetDateTime>,
+    pub from_id: String,
+    pub message_body: String,
+    pub message_content_type: i32,
+    pub message_time: OffsetDateTime,
+}
+
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct AddGroupMemberRequest {
     #[allow(dead_code)]
@@ -166,6 +442,38 @@ pub struct AddGroupMemberRequest {
     pub alias: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, ToSchema, Default)]
+pub struct GroupJoinRequest {
+    /// 申请附言
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct HandleGroupJoinRequest {
+    pub approve_status: i32, // 1=同意，2=拒绝
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema, Default)]
+pub struct GetGroupJoinRequestsQuery {
+    pub approve_status: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateGroupInviteRequest {
+    /// 有效期（秒），为空表示永不过期
+    pub expire_seconds: Option<i64>,
+    /// 最大使用次数，为空表示不限
+    pub max_uses: Option<i32>,
+    /// 通过邀请加入时是否仍需审批
+    #[serde(default)]
+    pub require_approval: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct JoinByInviteRequest {
+    pub invite_token: String,
+}
+
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct CreateGroupRequest {
     pub group_id: String,
@@ -213,3 +521,11 @@ pub struct UploadResp {
     pub file_name: String,
     pub file_type: String,
 }
+
+/// 管理员更新用户状态标识，未传的字段保持不变
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateUserFlagsReq {
+    pub forbidden_flag: Option<i32>,
+    pub silent_flag: Option<i32>,
+    pub disable_add_friend: Option<i32>,
+}
@@ -0,0 +1,247 @@
+//! 群消息扇出：发送接口在消息持久化后提交任务，由后台有界并发地推送给群成员
+
+use crate::config::FanoutConfig;
+use crate::models::{ChatMessage, GroupMessageNotice};
+use crate::mqtt;
+use crate::prelude::*;
+use im_share::redis::RedisClient;
+use salvo::oapi::ToSchema;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::{Semaphore, mpsc};
+use tokio::task::JoinSet;
+
+/// 扇出任务：同一条消息推送给一组接收者
+#[derive(Debug)]
+pub struct FanoutJob {
+    pub group_id: String,
+    pub payload: FanoutPayload,
+    /// 接收者 open_id（已排除发送者并去重）
+    pub recipients: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum FanoutPayload {
+    /// 写扩散：推送完整消息，并写入每个成员的 Redis 离线消息
+    Message(ChatMessage),
+    /// 读扩散：只推送新消息通知，不写离线消息，成员按序列号拉取
+    Notice(GroupMessageNotice),
+}
+
+impl FanoutPayload {
+    fn message_id(&self) -> &str {
+        match self {
+            FanoutPayload::Message(message) => &message.message_id,
+            FanoutPayload::Notice(notice) => &notice.message_id,
+        }
+    }
+
+    fn encode(&self) -> serde_json::Result<String> {
+        match self {
+            FanoutPayload::Message(message) => serde_json::to_string(message),
+            FanoutPayload::Notice(notice) => serde_json::to_string(notice),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FanoutMetrics {
+    jobs_submitted: AtomicU64,
+    jobs_completed: AtomicU64,
+    jobs_in_flight: AtomicU64,
+    read_diffusion_jobs: AtomicU64,
+    batches: AtomicU64,
+    published: AtomicU64,
+    publish_failed: AtomicU64,
+    offline_stored: AtomicU64,
+    offline_failed: AtomicU64,
+}
+
+/// 扇出指标快照
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FanoutMetricsSnapshot {
+    /// 队列容量
+    pub queue_capacity: usize,
+    /// 队列中等待执行的任务数
+    pub queued: usize,
+    /// 正在执行的任务数
+    pub in_flight: u64,
+    /// 累计提交的任务数
+    pub jobs_submitted: u64,
+    /// 累计完成的任务数
+    pub jobs_completed: u64,
+    /// 累计完成的读扩散任务数
+    pub read_diffusion_jobs: u64,
+    /// 累计执行的批次数
+    pub batches: u64,
+    /// MQTT 发布成功数
+    pub published: u64,
+    /// MQTT 发布失败数
+    pub publish_failed: u64,
+    /// Redis 离线消息写入成功数
+    pub offline_stored: u64,
+    /// Redis 离线消息写入失败数
+    pub offline_failed: u64,
+}
+
+static SENDER: OnceLock<mpsc::Sender<FanoutJob>> = OnceLock::new();
+static METRICS: OnceLock<FanoutMetrics> = OnceLock::new();
+
+fn metrics() -> &'static FanoutMetrics {
+    METRICS.get_or_init(FanoutMetrics::default)
+}
+
+pub fn init(config: &FanoutConfig) -> anyhow::Result<()> {
+    let (sender, receiver) = mpsc::channel(config.queue_size.max(1));
+    SENDER
+        .set(sender)
+        .map_err(|_| anyhow::anyhow!("fanout should be set"))?;
+    tokio::spawn(dispatch(
+        receiver,
+        config.concurrency.max(1),
+        config.batch_size.max(1),
+    ));
+    Ok(())
+}
+
+/// 提交扇出任务，队列满时等待（背压）
+pub async fn submit(job: FanoutJob) -> AppResult<()> {
+    if job.recipients.is_empty() {
+        return Ok(());
+    }
+    let sender = SENDER.get().expect("fanout should be initialized");
+    sender
+        .send(job)
+        .await
+        .map_err(|_| AppError::internal("消息推送队列已关闭"))?;
+    metrics().jobs_submitted.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+/// 获取扇出指标
+pub fn snapshot() -> FanoutMetricsSnapshot {
+    let metrics = metrics();
+    let (queue_capacity, queued) = SENDER
+        .get()
+        .map(|sender| {
+            (
+                sender.max_capacity(),
+                sender.max_capacity() - sender.capacity(),
+            )
+        })
+        .unwrap_or_default();
+    FanoutMetricsSnapshot {
+        queue_capacity,
+        queued,
+        in_flight: metrics.jobs_in_flight.load(Ordering::Relaxed),
+        jobs_submitted: metrics.jobs_submitted.load(Ordering::Relaxed),
+        jobs_completed: metrics.jobs_completed.load(Ordering::Relaxed),
+        read_diffusion_jobs: metrics.read_diffusion_jobs.load(Ordering::Relaxed),
+        batches: metrics.batches.load(Ordering::Relaxed),
+        published: metrics.published.load(Ordering::Relaxed),
+        publish_failed: metrics.publish_failed.load(Ordering::Relaxed),
+        offline_stored: metrics.offline_stored.load(Ordering::Relaxed),
+        offline_failed: metrics.offline_failed.load(Ordering::Relaxed),
+    }
+}
+
+async fn dispatch(mut receiver: mpsc::Receiver<FanoutJob>, concurrency: usize, batch_size: usize) {
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    while let Some(job) = receiver.recv().await {
+        let permit = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("fanout semaphore should not be closed");
+        tokio::spawn(async move {
+            run_job(job, batch_size).await;
+            drop(permit);
+        });
+    }
+}
+
+async fn run_job(job: FanoutJob, batch_size: usize) {
+    let metrics = metrics();
+    metrics.jobs_in_flight.fetch_add(1, Ordering::Relaxed);
+
+    match job.payload.encode() {
+        Ok(payload) => {
+            for batch in job.recipients.chunks(batch_size) {
+                deliver_batch(&job, batch, &payload).await;
+                metrics.batches.fetch_add(1, Ordering::Relaxed);
+            }
+            info!(
+                group_id = %job.group_id,
+                message_id = %job.payload.message_id(),
+                recipients = job.recipients.len(),
+                "群消息扇出完成"
+            );
+        }
+        Err(e) => {
+            error!(group_id = %job.group_id, message_id = %job.payload.message_id(), error = %e, "群消息编码失败");
+        }
+    }
+
+    if let FanoutPayload::Notice(_) = job.payload {
+        metrics.read_diffusion_jobs.fetch_add(1, Ordering::Relaxed);
+    }
+    metrics.jobs_in_flight.fetch_sub(1, Ordering::Relaxed);
+    metrics.jobs_completed.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 推送一批成员：MQTT 并发发布，Redis 离线备份合并为一次往返
+///
+/// 写扩散时 MQTT 发布成功与否都写入 Redis，用户在发布之后才连接时也能拉取到消息
+async fn deliver_batch(job: &FanoutJob, batch: &[String], payload: &str) {
+    let metrics = metrics();
+    let publisher = mqtt::get_mqtt_publisher();
+
+    let mut publishes = JoinSet::new();
+    for open_id in batch {
+        let topic = format!("user/{}", open_id);
+        let payload = payload.as_bytes().to_vec();
+        publishes.spawn(async move {
+            let result = publisher.publish(&topic, payload).await;
+            (topic, result)
+        });
+    }
+    while let Some(result) = publishes.join_next().await {
+        match result {
+            Ok((_, Ok(()))) => {
+                metrics.published.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok((topic, Err(e))) => {
+                metrics.publish_failed.fetch_add(1, Ordering::Relaxed);
+                warn!(group_id = %job.group_id, %topic, error = %e, message_id = %job.payload.message_id(), "消息MQTT发布失败");
+            }
+            Err(e) => {
+                metrics.publish_failed.fetch_add(1, Ordering::Relaxed);
+                error!(group_id = %job.group_id, error = %e, "MQTT 发布任务异常");
+            }
+        }
+    }
+
+    if let FanoutPayload::Notice(_) = job.payload {
+        return;
+    }
+    match RedisClient::add_offline_messages(batch, payload).await {
+        Ok(()) => {
+            metrics
+                .offline_stored
+                .fetch_add(batch.len() as u64, Ordering::Relaxed);
+        }
+        Err(e) => {
+            metrics
+                .offline_failed
+                .fetch_add(batch.len() as u64, Ordering::Relaxed);
+            warn!(
+                group_id = %job.group_id,
+                message_id = %job.payload.message_id(),
+                batch_size = batch.len(),
+                error = %e,
+                "Redis 离线消息批量存储失败（消息已保存到数据库，不会丢失）"
+            );
+        }
+    }
+}
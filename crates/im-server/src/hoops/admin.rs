@@ -0,0 +1,25 @@
+use crate::config;
+use crate::models::User;
+use crate::prelude::*;
+use salvo::prelude::*;
+
+/// 管理员校验，需挂在 auth_hoop 之后
+#[handler]
+pub async fn admin_hoop(
+    req: &mut Request,
+    depot: &mut Depot,
+    res: &mut Response,
+    ctrl: &mut FlowCtrl,
+) {
+    let is_admin = depot
+        .obtain::<User>()
+        .map(|user| config::get().auth.admin_open_ids.contains(&user.open_id))
+        .unwrap_or(false);
+
+    if !is_admin {
+        AppError::unauthorized("无管理员权限")
+            .write(req, depot, res)
+            .await;
+        ctrl.skip_rest();
+    }
+}
@@ -33,7 +33,21 @@ pub async fn auth_hoop(
     if let Some(token) = token {
         match verify_token(&token, &config::get().jwt) {
             Ok(data) => {
+                if service::auth_service::is_token_revoked(&data).await {
+                    depot.insert(jwt_auth::JWT_AUTH_STATE_KEY, JwtAuthState::Forbidden);
+                    AppError::unauthorized("登录已失效，请重新登录")
+                        .write(req, depot, res)
+                        .await;
+                    ctrl.skip_rest();
+                    return;
+                }
                 let open_id = data.open_id.to_string();
+                if let Err(e) = service::im_user_service::ensure_not_forbidden(&open_id).await {
+                    depot.insert(jwt_auth::JWT_AUTH_STATE_KEY, JwtAuthState::Forbidden);
+                    e.write(req, depot, res).await;
+                    ctrl.skip_rest();
+                    return;
+                }
                 let user = service::user_service::get_by_open_id(&open_id).await;
                 if let Ok(user) = user {
                     depot.inject(user);
@@ -3,9 +3,11 @@ use salvo::{
     prelude::*,
 };
 
+mod admin;
 mod cors;
 mod jwt;
 
+pub use admin::admin_hoop;
 pub use cors::cors_hoop;
 pub use jwt::auth_hoop;
 
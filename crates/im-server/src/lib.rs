@@ -3,9 +3,11 @@ pub mod config;
 pub mod db;
 pub mod dto;
 pub mod error;
+pub mod fanout;
 pub mod hoops;
 pub mod models;
 pub mod mqtt;
+pub mod notifier;
 pub mod prelude;
 pub mod routers;
 pub mod service;
@@ -2,6 +2,7 @@ use std::process::exit;
 use std::sync::Arc;
 use std::time::Duration;
 
+use im_server::service::im_friendship_service;
 use im_server::{mqtt, prelude::*};
 use im_share::redis::init_redis_client;
 use im_share::subscription::SubscriptionService;
@@ -34,6 +35,19 @@ async fn main() {
         .map_err(|e| format!("mqtt init error: {}", e))
         .unwrap();
 
+    im_server::notifier::init(&config.notifier)
+        .map_err(|e| format!("notifier init error: {}", e))
+        .unwrap();
+
+    im_server::fanout::init(&config.fanout)
+        .map_err(|e| format!("fanout init error: {}", e))
+        .unwrap();
+
+    tokio::spawn(expire_friendship_requests(
+        config.friendship.request_expire_days,
+        config.friendship.request_expire_interval,
+    ));
+
     let router = im_server::routers::root();
     info!("{config:#?}");
     info!("{router:?}");
@@ -58,6 +72,19 @@ async fn main() {
     server.serve(service).await
 }
 
+/// 定期将超时未处理的好友申请标记为已过期
+async fn expire_friendship_requests(expire_days: i64, interval_secs: u64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+    loop {
+        interval.tick().await;
+        match im_friendship_service::expire_friendship_requests(expire_days).await {
+            Ok(0) => {}
+            Ok(count) => info!(count, "已将超时的好友申请标记为过期"),
+            Err(e) => warn!(error = ?e, "处理过期好友申请失败"),
+        }
+    }
+}
+
 async fn shutdown_signal(handle: ServerHandle) {
     let ctrl_c = async {
         signal::ctrl_c()
@@ -0,0 +1,33 @@
+use salvo::oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ImFriendCategory {
+    pub category_id: String,
+    pub owner_id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_time: Option<OffsetDateTime>,
+    pub del_flag: i16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
+}
+
+impl ImFriendCategory {
+    pub fn validate_name(name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("分组名称不能为空".to_string());
+        }
+        if name.chars().count() > 50 {
+            return Err("分组名称不能超过50个字符".to_string());
+        }
+        Ok(())
+    }
+}
@@ -29,6 +29,27 @@ pub struct ImFriendship {
     pub version: Option<i64>,
 }
 
+/// 好友申请审批状态：待处理
+pub const APPROVE_STATUS_PENDING: i32 = 0;
+/// 好友申请审批状态：已同意
+pub const APPROVE_STATUS_APPROVED: i32 = 1;
+/// 好友申请审批状态：已拒绝
+pub const APPROVE_STATUS_REJECTED: i32 = 2;
+/// 好友申请审批状态：已过期
+pub const APPROVE_STATUS_EXPIRED: i32 = 3;
+/// 好友申请审批状态：已撤回
+pub const APPROVE_STATUS_WITHDRAWN: i32 = 4;
+
+/// 好友推荐
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct FriendRecommendation {
+    pub user_id: String,
+    /// 共同好友数
+    pub mutual_friend_count: i64,
+    /// 共同群组数
+    pub shared_group_count: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct ImFriendshipRequest {
     pub id: String,
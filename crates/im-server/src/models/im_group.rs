@@ -72,6 +72,11 @@ impl ImGroup {
 
         Ok(())
     }
+
+    /// 是否开启全员禁言
+    pub fn is_all_muted(&self) -> bool {
+        self.mute == Some(0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
@@ -84,6 +89,8 @@ pub struct ImGroupMember {
     pub speak_date: Option<OffsetDateTime>,
     pub mute: i16,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub mute_end_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alias: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub join_time: Option<OffsetDateTime>,
@@ -101,3 +108,115 @@ pub struct ImGroupMember {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<i64>,
 }
+
+/// 群成员解析结果：成员ID对应的用户 open_id 和数据库ID，用于消息扇出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedGroupMember {
+    pub member_id: String,
+    pub open_id: String,
+    pub user_id: i64,
+}
+
+impl ImGroupMember {
+    /// 成员当前是否处于禁言状态，禁言截止时间已过视为自动解除
+    pub fn is_muted(&self, now: OffsetDateTime) -> bool {
+        self.mute == 0 && self.mute_end_time.is_none_or(|end_time| end_time > now)
+    }
+}
+
+/// 群成员角色（群主 > 管理员 > 普通成员）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GroupRole {
+    Member = 0,
+    Admin = 1,
+    Owner = 2,
+}
+
+impl GroupRole {
+    pub fn from_role(role: i32) -> Option<Self> {
+        match role {
+            0 => Some(GroupRole::Member),
+            1 => Some(GroupRole::Admin),
+            2 => Some(GroupRole::Owner),
+            _ => None,
+        }
+    }
+
+    /// 解析角色名称（owner/admin/member），用于查询参数
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "owner" => Some(GroupRole::Owner),
+            "admin" => Some(GroupRole::Admin),
+            "member" => Some(GroupRole::Member),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GroupRole::Member => "普通成员",
+            GroupRole::Admin => "管理员",
+            GroupRole::Owner => "群主",
+        }
+    }
+}
+
+/// 公开群（可被搜索）
+pub const GROUP_TYPE_PUBLIC: i32 = 2;
+
+/// 每个群最多可设置的标签数
+pub const MAX_GROUP_TAG_COUNT: usize = 10;
+
+/// 单个标签最大长度（字符）
+pub const MAX_GROUP_TAG_CHARS: usize = 20;
+
+/// 规范化群标签：去除首尾空白、转为小写并去重，保持原有顺序
+pub fn normalize_group_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() {
+            return Err("标签不能为空".to_string());
+        }
+        if tag.chars().count() > MAX_GROUP_TAG_CHARS {
+            return Err(format!("标签不能超过{}个字符", MAX_GROUP_TAG_CHARS));
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_GROUP_TAG_COUNT {
+        return Err(format!("标签数量不能超过{}个", MAX_GROUP_TAG_COUNT));
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_normalize_group_tags() {
+        assert_eq!(
+            normalize_group_tags(&tags(&[" Rust ", "rust", "后端"])).unwrap(),
+            tags(&["rust", "后端"])
+        );
+        assert!(normalize_group_tags(&tags(&["  "])).is_err());
+        assert!(normalize_group_tags(&tags(&[&"标".repeat(MAX_GROUP_TAG_CHARS + 1)])).is_err());
+
+        let too_many: Vec<String> = (0..=MAX_GROUP_TAG_COUNT).map(|i| i.to_string()).collect();
+        assert!(normalize_group_tags(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_group_role_from_name() {
+        assert_eq!(GroupRole::from_name("Owner"), Some(GroupRole::Owner));
+        assert_eq!(GroupRole::from_name(" admin "), Some(GroupRole::Admin));
+        assert_eq!(GroupRole::from_name("member"), Some(GroupRole::Member));
+        assert_eq!(GroupRole::from_name("guest"), None);
+    }
+}
@@ -0,0 +1,49 @@
+use salvo::oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+/// 群公告内容最大长度（字符）
+pub const ANNOUNCEMENT_MAX_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ImGroupAnnouncement {
+    pub announcement_id: String,
+    pub group_id: String,
+    pub author_id: String,
+    pub content: String,
+    /// 是否置顶（1置顶，0不置顶）
+    pub pinned: i16,
+    pub del_flag: i16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_time: Option<OffsetDateTime>,
+}
+
+impl ImGroupAnnouncement {
+    pub fn validate_content(content: &str) -> Result<(), String> {
+        let content = content.trim();
+        if content.is_empty() {
+            return Err("公告内容不能为空".to_string());
+        }
+        if content.chars().count() > ANNOUNCEMENT_MAX_CHARS {
+            return Err(format!("公告内容不能超过{}个字符", ANNOUNCEMENT_MAX_CHARS));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_content() {
+        assert!(ImGroupAnnouncement::validate_content("  ").is_err());
+        assert!(ImGroupAnnouncement::validate_content("本周五例会").is_ok());
+        let long = "公".repeat(ANNOUNCEMENT_MAX_CHARS);
+        assert!(ImGroupAnnouncement::validate_content(&long).is_ok());
+        assert!(ImGroupAnnouncement::validate_content(&format!("{long}告")).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use salvo::oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+/// 封禁原因最大长度（字符）
+pub const BAN_REASON_MAX_CHARS: usize = 255;
+
+/// 群黑名单：被封禁的用户在解除前不能以任何方式重新入群
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ImGroupBan {
+    pub group_id: String,
+    pub member_id: String,
+    pub operator_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// 解除时间，为空表示永久
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<OffsetDateTime>,
+}
+
+impl ImGroupBan {
+    /// 封禁是否仍然生效，解除时间已过视为自动解除
+    pub fn is_active(&self, now: OffsetDateTime) -> bool {
+        self.expire_time.is_none_or(|expire_time| expire_time > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    #[test]
+    fn test_is_active() {
+        let now = OffsetDateTime::now_utc();
+        let mut ban = ImGroupBan {
+            group_id: "group".to_string(),
+            member_id: "user".to_string(),
+            operator_id: "owner".to_string(),
+            reason: None,
+            expire_time: None,
+            create_time: None,
+        };
+        assert!(ban.is_active(now));
+
+        ban.expire_time = Some(now + Duration::hours(1));
+        assert!(ban.is_active(now));
+
+        ban.expire_time = Some(now - Duration::seconds(1));
+        assert!(!ban.is_active(now));
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+/// 群系统消息的消息类型
+pub const GROUP_SYSTEM_MESSAGE_TYPE: i32 = 100;
+
+/// 群系统事件，序列化后作为系统消息的 message_body
+///
+/// 客户端根据 type 字段和结构化参数渲染本地化文本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GroupSystemEvent {
+    /// 成员入群，operator_id 为邀请人或审批人，自由加入时为空
+    GroupMemberJoined {
+        group_id: String,
+        member_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        operator_id: Option<String>,
+    },
+    /// 成员主动退群
+    GroupMemberLeft { group_id: String, member_id: String },
+    /// 成员被移出群组
+    GroupMemberKicked {
+        group_id: String,
+        member_id: String,
+        operator_id: String,
+    },
+    /// 成员角色变更（0普通成员，1管理员）
+    GroupMemberRoleChanged {
+        group_id: String,
+        member_id: String,
+        operator_id: String,
+        role: i32,
+    },
+    /// 群名称变更
+    GroupRenamed {
+        group_id: String,
+        operator_id: String,
+        group_name: String,
+    },
+    /// 群公告变更
+    GroupAnnouncementChanged {
+        group_id: String,
+        operator_id: String,
+        notification: String,
+    },
+    /// 发布新群公告
+    GroupAnnouncementPublished {
+        group_id: String,
+        operator_id: String,
+        announcement_id: String,
+        content: String,
+        pinned: bool,
+    },
+    /// 开启全员禁言
+    GroupMuted {
+        group_id: String,
+        operator_id: String,
+    },
+    /// 关闭全员禁言
+    GroupUnmuted {
+        group_id: String,
+        operator_id: String,
+    },
+    /// 成员被禁言，mute_end_time 为毫秒时间戳，为空表示永久禁言
+    MemberMuted {
+        group_id: String,
+        operator_id: String,
+        member_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mute_end_time: Option<i64>,
+    },
+    /// 成员被解除禁言
+    MemberUnmuted {
+        group_id: String,
+        operator_id: String,
+        member_id: String,
+    },
+    /// 消息被置顶，单聊和群聊共用，chat_id 为会话ID
+    MessagePinned {
+        chat_id: String,
+        operator_id: String,
+        message_id: String,
+    },
+    /// 消息被取消置顶
+    MessageUnpinned {
+        chat_id: String,
+        operator_id: String,
+        message_id: String,
+    },
+    /// 群主转让
+    GroupOwnerTransferred {
+        group_id: String,
+        old_owner_id: String,
+        new_owner_id: String,
+    },
+    /// 群组解散
+    GroupDissolved {
+        group_id: String,
+        group_name: String,
+        owner_id: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json_shape() {
+        let event = GroupSystemEvent::GroupMemberKicked {
+            group_id: "g1".to_string(),
+            member_id: "u2".to_string(),
+            operator_id: "u1".to_string(),
+        };
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "type": "group_member_kicked",
+                "group_id": "g1",
+                "member_id": "u2",
+                "operator_id": "u1",
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<GroupSystemEvent>(value).unwrap(),
+            event
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use salvo::oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ImGroupInvite {
+    pub invite_token: String,
+    pub group_id: String,
+    pub creator_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<i32>,
+    pub use_count: i32,
+    pub require_approval: i16,
+    pub revoked: i16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_time: Option<OffsetDateTime>,
+}
+
+impl ImGroupInvite {
+    /// 邀请是否仍可使用
+    pub fn is_usable(&self, now: OffsetDateTime) -> bool {
+        self.revoked == 0
+            && self.expire_time.is_none_or(|expire_time| expire_time > now)
+            && self
+                .max_uses
+                .is_none_or(|max_uses| self.use_count < max_uses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    fn invite() -> ImGroupInvite {
+        ImGroupInvite {
+            invite_token: "token".to_string(),
+            group_id: "group".to_string(),
+            creator_id: "owner".to_string(),
+            expire_time: None,
+            max_uses: None,
+            use_count: 0,
+            require_approval: 0,
+            revoked: 0,
+            create_time: None,
+            update_time: None,
+        }
+    }
+
+    #[test]
+    fn test_is_usable() {
+        let now = OffsetDateTime::now_utc();
+        assert!(invite().is_usable(now));
+
+        let mut revoked = invite();
+        revoked.revoked = 1;
+        assert!(!revoked.is_usable(now));
+
+        let mut expired = invite();
+        expired.expire_time = Some(now - Duration::seconds(1));
+        assert!(!expired.is_usable(now));
+
+        let mut used_up = invite();
+        used_up.max_uses = Some(2);
+        used_up.use_count = 2;
+        assert!(!used_up.is_usable(now));
+        used_up.use_count = 1;
+        assert!(used_up.is_usable(now));
+    }
+}
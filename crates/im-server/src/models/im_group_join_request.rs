@@ -0,0 +1,30 @@
+use salvo::oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+/// 加群申请审批状态：待处理
+pub const JOIN_STATUS_PENDING: i32 = 0;
+/// 加群申请审批状态：已同意
+pub const JOIN_STATUS_APPROVED: i32 = 1;
+/// 加群申请审批状态：已拒绝
+pub const JOIN_STATUS_REJECTED: i32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ImGroupJoinRequest {
+    pub request_id: String,
+    pub group_id: String,
+    pub from_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub approve_status: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handler_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_time: Option<OffsetDateTime>,
+    pub del_flag: i16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
+}
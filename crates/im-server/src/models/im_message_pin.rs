@@ -0,0 +1,16 @@
+use salvo::oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+/// 每个会话最多置顶的消息数
+pub const MAX_PINS_PER_CHAT: i64 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ImMessagePin {
+    pub chat_id: String,
+    pub message_id: String,
+    pub pinned_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pin_time: Option<OffsetDateTime>,
+}
@@ -52,6 +52,13 @@ impl From<ImUser> for ImSafeUser {
     }
 }
 
+/// 加好友验证类型：无需验证，直接成为好友
+pub const FRIEND_ALLOW_NO_VERIFY: i32 = 1;
+/// 加好友验证类型：需要验证
+pub const FRIEND_ALLOW_VERIFY: i32 = 2;
+/// 加好友验证类型：不允许任何人添加
+pub const FRIEND_ALLOW_NOBODY: i32 = 3;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct ImUserData {
     pub user_id: String,
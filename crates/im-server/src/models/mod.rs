@@ -1,5 +1,5 @@
 pub mod im_friendship;
-pub use im_friendship::{ImFriendship, ImFriendshipRequest};
+pub use im_friendship::{FriendRecommendation, ImFriendship, ImFriendshipRequest};
 
 pub mod im_group_message;
 pub use im_group_message::ImGroupMessage;
@@ -11,7 +11,7 @@ pub mod im_user;
 pub use im_user::{ImSafeUser, ImUser, ImUserData};
 
 pub mod share;
-pub use share::ChatMessage;
+pub use share::{ChatMessage, GroupMessageNotice};
 
 pub mod response;
 pub use response::MyResponse;
@@ -20,10 +20,34 @@ pub mod user;
 pub use user::{SafeUser, User};
 
 pub mod im_group;
-pub use im_group::{ImGroup, ImGroupMember};
+pub use im_group::{GroupRole, ImGroup, ImGroupMember, ResolvedGroupMember};
+
+pub mod im_group_join_request;
+pub use im_group_join_request::ImGroupJoinRequest;
+
+pub mod im_group_invite;
+pub use im_group_invite::ImGroupInvite;
+
+pub mod im_group_announcement;
+pub use im_group_announcement::ImGroupAnnouncement;
+
+pub mod im_group_ban;
+pub use im_group_ban::ImGroupBan;
+
+pub mod im_message_pin;
+pub use im_message_pin::ImMessagePin;
+
+pub mod im_group_event;
+pub use im_group_event::{GROUP_SYSTEM_MESSAGE_TYPE, GroupSystemEvent};
 
 pub mod im_outbox;
 pub use im_outbox::ImOutbox;
 
 pub mod im_chat;
 pub use im_chat::{ChatWithName, ImChat};
+
+pub mod user_totp;
+pub use user_totp::UserTotp;
+
+pub mod im_friend_category;
+pub use im_friend_category::ImFriendCategory;
@@ -1,8 +1,3 @@
-use std::{
-    collections::HashMap,
-    sync::{LazyLock, RwLock},
-};
-
 use salvo::oapi::ToSchema;
 use serde::{Deserialize, Serialize};
 
@@ -44,26 +39,13 @@ pub struct SendRequest {
     pub file_type: Option<String>,
 }
 
-static GROUP_MEMBERS: LazyLock<RwLock<HashMap<String, Vec<String>>>> = LazyLock::new(|| {
-    let mut map = HashMap::new();
-    map.insert(
-        "g1".to_string(),
-        vec!["u1".to_string(), "u2".to_string(), "u3".to_string()],
-    );
-    map.insert("g2".to_string(), vec!["u2".to_string(), "u4".to_string()]);
-    RwLock::new(map)
-});
-
-pub fn get_group_members(group_id: &str) -> Vec<String> {
-    GROUP_MEMBERS
-        .read()
-        .ok()
-        .and_then(|m| m.get(group_id).cloned())
-        .unwrap_or_default()
-}
-
-pub fn set_group_members(group_id: &str, members: Vec<String>) {
-    if let Ok(mut m) = GROUP_MEMBERS.write() {
-        m.insert(group_id.to_string(), members);
-    }
+/// 大群新消息通知（读扩散）：不携带消息内容，客户端收到后按序列号拉取
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "group_new_messages")]
+pub struct GroupMessageNotice {
+    pub group_id: String,
+    /// 群内最新消息序列号
+    pub sequence: i64,
+    pub message_id: String,
+    pub timestamp_ms: i64,
 }
@@ -0,0 +1,71 @@
+use super::Notifier;
+use salvo::async_trait;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+use tokio::io::AsyncWriteExt;
+
+/// 本地开发/测试使用：通知内容写入日志并追加到文件
+pub struct FileNotifier {
+    path: PathBuf,
+}
+
+impl FileNotifier {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl Notifier for FileNotifier {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> anyhow::Result<()> {
+        tracing::info!(to = %to, subject = %subject, "notifier: {}", body);
+
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        let line = format!(
+            "{}\t{}\t{}\t{}\n",
+            OffsetDateTime::now_utc().unix_timestamp(),
+            to,
+            subject,
+            body.replace('\n', " ")
+        );
+        file.write_all(line.as_bytes()).await?;
+        // tokio 的 File 在后台线程写入，需 flush 确保返回前已落盘
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_notifier_append() {
+        let path = std::env::temp_dir().join(format!("notifier-{}.log", ulid::Ulid::new()));
+        let notifier = FileNotifier::new(&path);
+        notifier
+            .send("a@example.com", "s1", "123456")
+            .await
+            .unwrap();
+        notifier
+            .send("b@example.com", "s2", "654321")
+            .await
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("a@example.com\ts1\t123456"));
+        assert!(lines[1].ends_with("b@example.com\ts2\t654321"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,43 @@
+mod file;
+mod smtp;
+
+use crate::config::NotifierConfig;
+use salvo::async_trait;
+use std::sync::OnceLock;
+
+pub use file::FileNotifier;
+pub use smtp::SmtpNotifier;
+
+/// 通知发送（验证码等），不同环境使用不同实现
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> anyhow::Result<()>;
+}
+
+pub static NOTIFIER: OnceLock<Box<dyn Notifier>> = OnceLock::new();
+
+pub fn init(config: &NotifierConfig) -> anyhow::Result<()> {
+    let notifier: Box<dyn Notifier> = match config.kind.as_str() {
+        "smtp" => {
+            let smtp = config
+                .smtp
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("notifier.smtp is required when kind = smtp"))?;
+            Box::new(SmtpNotifier::new(smtp)?)
+        }
+        "file" => Box::new(FileNotifier::new(&config.file_path)),
+        other => anyhow::bail!("unknown notifier kind: {}", other),
+    };
+
+    NOTIFIER
+        .set(notifier)
+        .map_err(|_| anyhow::anyhow!("notifier should be set"))?;
+    Ok(())
+}
+
+pub fn get() -> &'static dyn Notifier {
+    NOTIFIER
+        .get()
+        .expect("notifier should be initialized")
+        .as_ref()
+}
@@ -1,5 +1,5 @@
 use crate::api::*;
-use crate::hoops::auth_hoop;
+use crate::hoops::{admin_hoop, auth_hoop};
 use salvo::prelude::*;
 
 pub fn root() -> Router {
@@ -16,8 +16,17 @@ pub fn create_router() -> Vec<Router> {
         .push(Router::with_path("download/{open_id}/{*+file_name}").get(upload_api::get_file))
         .push(
             Router::with_path("auth")
-                .push(Router::with_path("login").post(auth_api::post_login))
-                .push(Router::with_path("register").post(auth_api::register)),
+                .push(
+                    Router::with_path("login")
+                        .post(auth_api::post_login)
+                        .push(Router::with_path("2fa").post(auth_api::post_login_two_factor)),
+                )
+                .push(Router::with_path("register").post(auth_api::register))
+                .push(
+                    Router::with_path("password-reset")
+                        .post(auth_api::request_password_reset)
+                        .push(Router::with_path("confirm").post(auth_api::reset_password)),
+                ),
         )
         .push(
             Router::with_path("subscriptions/{subscription_id}/user")
@@ -34,6 +43,12 @@ pub fn create_router() -> Vec<Router> {
                 .post(user_api::create_user)
                 .get(user_api::list_users)
                 .put(user_api::update_current_user)
+                .push(Router::with_path("me/password").put(user_api::change_password))
+                .push(
+                    Router::with_path("me/2fa")
+                        .push(Router::with_path("enroll").post(user_api::enroll_totp))
+                        .push(Router::with_path("confirm").post(user_api::confirm_totp)),
+                )
                 .push(Router::with_path("{id}").get(user_api::get_user)),
         )
         .push(
@@ -59,6 +74,12 @@ pub fn create_router() -> Vec<Router> {
                                         .hoop(auth_hoop)
                                         .get(im_user_api::get_user_data)
                                         .put(im_user_api::upsert_user_data),
+                                )
+                                .push(
+                                    Router::with_path("flags")
+                                        .hoop(auth_hoop)
+                                        .hoop(admin_hoop)
+                                        .put(im_user_api::update_user_flags),
                                 ),
                         ),
                 )
@@ -72,16 +93,37 @@ pub fn create_router() -> Vec<Router> {
                         .hoop(auth_hoop)
                         .get(im_friendship_api::get_friends)
                         .post(im_friendship_api::add_friend)
+                        .push(Router::with_path("sync").get(im_friendship_api::sync_friends))
+                        .push(
+                            Router::with_path("recommendations")
+                                .get(im_friendship_api::get_recommendations),
+                        )
+                        .push(Router::with_path("black").get(im_friendship_api::get_black_list))
+                        .push(
+                            Router::with_path("categories")
+                                .get(im_friendship_api::get_friend_categories)
+                                .post(im_friendship_api::create_friend_category)
+                                .push(
+                                    Router::with_path("{category_id}")
+                                        .put(im_friendship_api::update_friend_category)
+                                        .delete(im_friendship_api::delete_friend_category),
+                                ),
+                        )
                         .push(
                             Router::with_path("{to_id}")
                                 .delete(im_friendship_api::remove_friend)
+                                .push(
+                                    Router::with_path("categories")
+                                        .put(im_friendship_api::set_friend_categories),
+                                )
                                 .push(
                                     Router::with_path("remark")
                                         .put(im_friendship_api::update_remark),
                                 )
                                 .push(
                                     Router::with_path("black")
-                                        .post(im_friendship_api::black_friend),
+                                        .post(im_friendship_api::black_friend)
+                                        .delete(im_friendship_api::unblock_friend),
                                 ),
                         ),
                 )
@@ -90,6 +132,10 @@ pub fn create_router() -> Vec<Router> {
                         .hoop(auth_hoop)
                         .get(im_group_api::get_user_groups)
                         .post(im_group_api::create_group)
+                        .push(
+                            Router::with_path("join-by-invite").post(im_group_api::join_by_invite),
+                        )
+                        .push(Router::with_path("search").get(im_group_api::search_groups))
                         .push(
                             Router::with_path("{group_id}")
                                 .get(im_group_api::get_group)
@@ -108,9 +154,76 @@ pub fn create_router() -> Vec<Router> {
                                                 .push(
                                                     Router::with_path("alias")
                                                         .put(im_group_api::update_member_alias),
+                                                )
+                                                .push(
+                                                    Router::with_path("mute")
+                                                        .put(im_group_api::set_member_mute),
+                                                ),
+                                        ),
+                                )
+                                .push(
+                                    Router::with_path("invites")
+                                        .get(im_group_api::get_group_invites)
+                                        .post(im_group_api::create_group_invite)
+                                        .push(
+                                            Router::with_path("{invite_token}")
+                                                .delete(im_group_api::revoke_group_invite),
+                                        ),
+                                )
+                                .push(
+                                    Router::with_path("bans")
+                                        .get(im_group_api::get_group_bans)
+                                        .push(
+                                            Router::with_path("{member_id}")
+                                                .put(im_group_api::ban_group_member)
+                                                .delete(im_group_api::unban_group_member),
+                                        ),
+                                )
+                                .push(
+                                    Router::with_path("avatar")
+                                        .get(im_group_api::get_group_avatar),
+                                )
+                                .push(
+                                    Router::with_path("tags")
+                                        .get(im_group_api::get_group_tags)
+                                        .put(im_group_api::set_group_tags),
+                                )
+                                .push(
+                                    Router::with_path("announcements")
+                                        .get(im_group_api::get_group_announcements)
+                                        .post(im_group_api::create_group_announcement)
+                                        .push(
+                                            Router::with_path("{announcement_id}")
+                                                .delete(im_group_api::delete_group_announcement)
+                                                .push(
+                                                    Router::with_path("pin").put(
+                                                        im_group_api::set_group_announcement_pinned,
+                                                    ),
+                                                )
+                                                .push(
+                                                    Router::with_path("ack")
+                                                        .get(im_group_api::get_group_announcement_acks)
+                                                        .post(
+                                                            im_group_api::acknowledge_group_announcement,
+                                                        ),
                                                 ),
                                         ),
                                 )
+                                .push(
+                                    Router::with_path("join-requests")
+                                        .get(im_group_api::get_group_join_requests)
+                                        .post(im_group_api::apply_join_group)
+                                        .push(
+                                            Router::with_path("{request_id}")
+                                                .post(im_group_api::handle_group_join_request),
+                                        ),
+                                )
+                                .push(Router::with_path("mute").put(im_group_api::set_group_mute))
+                                .push(
+                                    Router::with_path("transfer")
+                                        .post(im_group_api::transfer_group_owner),
+                                )
+                                .push(Router::with_path("leave").post(im_group_api::leave_group))
                                 .push(
                                     Router::with_path("dissolve")
                                         .delete(im_group_api::dissolve_group),
@@ -125,9 +238,18 @@ pub fn create_router() -> Vec<Router> {
                         .hoop(auth_hoop)
                         .get(im_friendship_api::get_friendship_requests)
                         .post(im_friendship_api::create_friendship_request)
+                        .push(
+                            Router::with_path("sent")
+                                .get(im_friendship_api::get_sent_friendship_requests),
+                        )
+                        .push(
+                            Router::with_path("read")
+                                .put(im_friendship_api::mark_friendship_requests_read),
+                        )
                         .push(
                             Router::with_path("{request_id}")
-                                .post(im_friendship_api::handle_friendship_request),
+                                .post(im_friendship_api::handle_friendship_request)
+                                .delete(im_friendship_api::withdraw_friendship_request),
                         ),
                 )
                 .push(
@@ -159,6 +281,11 @@ pub fn create_router() -> Vec<Router> {
                                             .get(im_message_api::get_user_group_message_status),
                                     ),
                             ),
+                        )
+                        .push(
+                            Router::with_path("fanout/metrics")
+                                .hoop(admin_hoop)
+                                .get(im_message_api::get_fanout_metrics),
                         ),
                 )
                 .push(
@@ -195,6 +322,15 @@ pub fn create_router() -> Vec<Router> {
                         .push(
                             Router::with_path("{chat_id}/read-sequence")
                                 .put(im_chat_api::update_read_sequence),
+                        )
+                        .push(
+                            Router::with_path("{chat_id}/pins")
+                                .get(im_chat_api::get_chat_pins)
+                                .push(
+                                    Router::with_path("{message_id}")
+                                        .put(im_chat_api::pin_chat_message)
+                                        .delete(im_chat_api::unpin_chat_message),
+                                ),
                         ),
                 ),
         );
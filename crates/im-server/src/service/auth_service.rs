@@ -1,7 +1,37 @@
-use crate::config::JwtConfig;
+use crate::config::{AuthConfig, JwtConfig};
+use crate::prelude::*;
+use crate::service::totp_service;
+use im_share::redis::RedisClient;
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use time::{Duration, UtcDateTime};
+
+// Redis 缓存键生成函数
+fn cache_key_revoked_before(open_id: &str) -> String {
+    format!("auth:revoked_before:{}", open_id)
+}
+
+fn cache_key_reset_code(open_id: &str) -> String {
+    format!("auth:reset_code:{}", open_id)
+}
+
+fn cache_key_reset_attempts(open_id: &str) -> String {
+    format!("auth:reset_attempts:{}", open_id)
+}
+
+fn cache_key_two_factor_challenge(challenge: &str) -> String {
+    format!("auth:2fa_challenge:{}", challenge)
+}
+
+fn cache_key_two_factor_attempts(challenge: &str) -> String {
+    format!("auth:2fa_attempts:{}", challenge)
+}
+
+fn cache_key_two_factor_failures(open_id: &str) -> String {
+    format!("auth:2fa_failures:{}", open_id)
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct JwtClaims {
     pub open_id: u64,
@@ -40,6 +70,158 @@ pub fn verify_token(token: &str, jwt_config: &JwtConfig) -> anyhow::Result<JwtCl
     Ok(claims.claims)
 }
 
+/// 吊销用户在此刻之前签发的所有 token
+/// 记录吊销时间点，保留到最长 token 过期为止
+pub async fn revoke_sessions(open_id: &str, jwt_config: &JwtConfig) -> AppResult<()> {
+    let now = UtcDateTime::now().unix_timestamp();
+    let ttl = (jwt_config.expiry.max(1) as u64) * 3600;
+    RedisClient::set_with_ttl(&cache_key_revoked_before(open_id), &now.to_string(), ttl)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to revoke sessions: {}", e)))
+}
+
+/// token 是否已被吊销（签发时间早于吊销时间点）
+pub async fn is_token_revoked(claims: &JwtClaims) -> bool {
+    let key = cache_key_revoked_before(&claims.open_id.to_string());
+    match RedisClient::get(&key).await {
+        Ok(Some(value)) => value
+            .parse::<i64>()
+            .map(|revoked_before| claims.iat < revoked_before)
+            .unwrap_or(false),
+        Ok(None) => false,
+        Err(e) => {
+            warn!(error = ?e, "获取 token 吊销状态失败");
+            false
+        }
+    }
+}
+
+/// 生成 6 位数字验证码
+pub fn generate_verification_code() -> String {
+    format!("{:06}", rand::rng().random_range(0..1_000_000))
+}
+
+/// 密码重置失败次数（按账号统计，重新获取验证码不会重置）
+async fn reset_attempts(open_id: &str) -> AppResult<i64> {
+    let attempts = RedisClient::get(&cache_key_reset_attempts(open_id))
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get reset attempts: {}", e)))?;
+    Ok(attempts.and_then(|v| v.parse().ok()).unwrap_or(0))
+}
+
+/// 生成密码重置验证码，覆盖之前未使用的验证码；账号处于锁定期时不再签发
+pub async fn issue_reset_code(open_id: &str, auth_config: &AuthConfig) -> AppResult<String> {
+    if reset_attempts(open_id).await? >= auth_config.reset_code_max_attempts {
+        return Err(AppError::public("验证码尝试次数过多，请稍后再试"));
+    }
+    let code = generate_verification_code();
+    RedisClient::set_with_ttl(
+        &cache_key_reset_code(open_id),
+        &code,
+        auth_config.reset_code_ttl,
+    )
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to save reset code: {}", e)))?;
+    Ok(code)
+}
+
+/// 校验并消费密码重置验证码（一次性）
+///
+/// 失败次数按账号在 reset_lockout_ttl 窗口内累计，超过最大尝试次数后验证码作废并锁定到窗口结束
+pub async fn consume_reset_code(
+    open_id: &str,
+    code: &str,
+    auth_config: &AuthConfig,
+) -> AppResult<()> {
+    let code_key = cache_key_reset_code(open_id);
+    let attempts_key = cache_key_reset_attempts(open_id);
+
+    let stored = RedisClient::get(&code_key)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get reset code: {}", e)))?
+        .ok_or_else(|| AppError::public("验证码无效或已过期"))?;
+
+    let attempts = RedisClient::incr_with_ttl(&attempts_key, auth_config.reset_lockout_ttl)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to count reset attempts: {}", e)))?;
+    if attempts > auth_config.reset_code_max_attempts {
+        let _ = RedisClient::del(&code_key).await;
+        return Err(AppError::public("验证码尝试次数过多，请稍后再试"));
+    }
+
+    if stored != code {
+        return Err(AppError::public("验证码错误"));
+    }
+
+    let _ = RedisClient::del_many(&[&code_key, &attempts_key]).await;
+    Ok(())
+}
+
+/// 密码校验通过但开启了两步验证时，签发短期登录挑战
+pub async fn create_two_factor_challenge(
+    open_id: &str,
+    auth_config: &AuthConfig,
+) -> AppResult<String> {
+    let challenge = ulid::Ulid::new().to_string();
+    RedisClient::set_with_ttl(
+        &cache_key_two_factor_challenge(&challenge),
+        open_id,
+        auth_config.two_factor_challenge_ttl,
+    )
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to save 2fa challenge: {}", e)))?;
+    Ok(challenge)
+}
+
+/// 使用挑战和两步验证码换取登录用户的 open_id（挑战一次性，超过最大尝试次数后作废）
+///
+/// 失败次数同时按账号在 two_factor_lockout_ttl 窗口内累计，避免通过反复登录获取新挑战来穷举验证码
+pub async fn consume_two_factor_challenge(
+    challenge: &str,
+    code: &str,
+    auth_config: &AuthConfig,
+) -> AppResult<String> {
+    let challenge_key = cache_key_two_factor_challenge(challenge);
+    let attempts_key = cache_key_two_factor_attempts(challenge);
+
+    let open_id = RedisClient::get(&challenge_key)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get 2fa challenge: {}", e)))?
+        .ok_or_else(|| AppError::unauthorized("登录验证已过期，请重新登录"))?;
+
+    let attempts = RedisClient::incr_with_ttl(&attempts_key, auth_config.two_factor_challenge_ttl)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to count 2fa attempts: {}", e)))?;
+    if attempts > auth_config.two_factor_max_attempts {
+        let _ = RedisClient::del_many(&[&challenge_key, &attempts_key]).await;
+        return Err(AppError::unauthorized("验证码尝试次数过多，请重新登录"));
+    }
+
+    let failures_key = cache_key_two_factor_failures(&open_id);
+    let failures = RedisClient::get(&failures_key)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get 2fa failures: {}", e)))?
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(0);
+    if failures >= auth_config.two_factor_max_failures {
+        let _ = RedisClient::del_many(&[&challenge_key, &attempts_key]).await;
+        return Err(AppError::unauthorized("验证失败次数过多，请稍后再试"));
+    }
+
+    if let Err(e) = totp_service::verify_code(&open_id, code).await {
+        // 只统计验证码错误，服务端错误不计入
+        if matches!(e, AppError::Public(_)) {
+            RedisClient::incr_with_ttl(&failures_key, auth_config.two_factor_lockout_ttl)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to count 2fa failures: {}", e)))?;
+        }
+        return Err(e);
+    }
+
+    let _ = RedisClient::del_many(&[&challenge_key, &attempts_key, &failures_key]).await;
+    Ok(open_id)
+}
+
 #[cfg(test)]
 mod tests {
     use time::OffsetDateTime;
@@ -53,4 +235,13 @@ mod tests {
             OffsetDateTime::now_utc().unix_timestamp()
         );
     }
+
+    #[test]
+    fn test_verification_code_format() {
+        for _ in 0..100 {
+            let code = generate_verification_code();
+            assert_eq!(code.len(), 6);
+            assert!(code.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
 }
@@ -3,6 +3,8 @@ use time::OffsetDateTime;
 
 use crate::db;
 use crate::models::SafeUser;
+use crate::service::im_friendship_service;
+
 pub async fn add_friend(user_id: &str, friend_id: &str) -> AppResult<()> {
     let conn = db::pool();
 
@@ -12,39 +14,28 @@ pub async fn add_friend(user_id: &str, friend_id: &str) -> AppResult<()> {
 
     // 插入好友关系，双向
     let timestamp = OffsetDateTime::now_utc();
-    sqlx::query!(
-        r#"
-            INSERT INTO im_friendship(owner_id, to_id, remark, del_flag, black , sequence, add_source, version)
-            VALUES($1, $2, NULL, 1, 1, $3, 'api' , 1)
-            ON CONFLICT(owner_id, to_id) DO UPDATE SET
-            del_flag = 1,
-            update_time = $4,
-            version = im_friendship.version + 1
-        "#,
-        user_id,
-        friend_id,
-        timestamp.unix_timestamp() * 1000,
-        timestamp
-    )
-    .execute(conn)
-    .await?;
-
-    sqlx::query!(
-        r#"
-            INSERT INTO im_friendship(owner_id, to_id, remark, del_flag, black , sequence, add_source, version)
-            VALUES($1, $2, NULL, 1, 1, $3,'api', 1)
-            ON CONFLICT(owner_id, to_id) DO UPDATE SET
-            del_flag = 1,
-            update_time = $4,
-            version = im_friendship.version + 1
-        "#,
-        friend_id,
-        user_id,
-        timestamp.unix_timestamp() * 1000,
-        timestamp
-    )
-    .execute(conn)
-    .await?;
+    let mut tx = conn.begin().await?;
+    for (owner_id, to_id) in [(user_id, friend_id), (friend_id, user_id)] {
+        let sequence = im_friendship_service::next_sequence(&mut tx, owner_id).await?;
+        sqlx::query!(
+            r#"
+                INSERT INTO im_friendship(owner_id, to_id, remark, del_flag, black , sequence, add_source, version)
+                VALUES($1, $2, NULL, 1, 1, $3, 'api' , 1)
+                ON CONFLICT(owner_id, to_id) DO UPDATE SET
+                del_flag = 1,
+                sequence = EXCLUDED.sequence,
+                update_time = $4,
+                version = im_friendship.version + 1
+            "#,
+            owner_id,
+            to_id,
+            sequence,
+            timestamp
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
 
     Ok(())
 }
@@ -53,18 +44,24 @@ pub async fn remove_friend(user_id: &str, friend_id: &str) -> AppResult<()> {
     let conn = db::pool();
 
     // 软删除双向关系（设置 del_flag = 0）
-    sqlx::query!(
-        r#"UPDATE im_friendship
-        SET del_flag = 0, update_time = $1, version = version + 1
-        WHERE((owner_id = $2 AND to_id = $3) OR (owner_id = $3 AND to_id = $2))
-        AND (del_flag IS NULL OR del_flag = 1)
-        "#,
-        OffsetDateTime::now_utc(),
-        user_id,
-        friend_id
-    )
-    .execute(conn)
-    .await?;
+    let mut tx = conn.begin().await?;
+    for (owner_id, to_id) in [(user_id, friend_id), (friend_id, user_id)] {
+        let sequence = im_friendship_service::next_sequence(&mut tx, owner_id).await?;
+        sqlx::query!(
+            r#"UPDATE im_friendship
+            SET del_flag = 0, sequence = $1, update_time = $2, version = version + 1
+            WHERE owner_id = $3 AND to_id = $4
+            AND (del_flag IS NULL OR del_flag = 1)
+            "#,
+            sequence,
+            OffsetDateTime::now_utc(),
+            owner_id,
+            to_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
 
     Ok(())
 }
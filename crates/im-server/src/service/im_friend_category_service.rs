@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use crate::db;
+use crate::models::ImFriendCategory;
+use crate::prelude::*;
+use crate::service::im_friendship_service;
+use time::OffsetDateTime;
+use ulid::Ulid;
+
+/// 每个用户最多可创建的好友分组数量
+const MAX_CATEGORY_COUNT: i64 = 50;
+
+/// 获取好友分组列表
+pub async fn get_categories(owner_id: &str) -> AppResult<Vec<ImFriendCategory>> {
+    let conn = db::pool();
+    let categories = sqlx::query_as!(
+        ImFriendCategory,
+        r#"
+            SELECT category_id, owner_id, name, sequence, create_time, update_time, del_flag, version
+            FROM im_friend_category
+            WHERE owner_id = $1 AND del_flag = 1
+            ORDER BY create_time ASC
+        "#,
+        owner_id
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(categories)
+}
+
+async fn get_category(owner_id: &str, category_id: &str) -> AppResult<ImFriendCategory> {
+    let conn = db::pool();
+    sqlx::query_as!(
+        ImFriendCategory,
+        r#"
+            SELECT category_id, owner_id, name, sequence, create_time, update_time, del_flag, version
+            FROM im_friend_category
+            WHERE category_id = $1 AND owner_id = $2 AND del_flag = 1
+        "#,
+        category_id,
+        owner_id
+    )
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::not_found("好友分组不存在"))
+}
+
+async fn ensure_name_available(
+    owner_id: &str,
+    name: &str,
+    exclude_category_id: Option<&str>,
+) -> AppResult<()> {
+    let conn = db::pool();
+    let count = sqlx::query_scalar!(
+        r#"
+            SELECT COUNT(*) as "count!: i64" FROM im_friend_category
+            WHERE owner_id = $1 AND name = $2 AND del_flag = 1
+            AND ($3::varchar IS NULL OR category_id != $3)
+        "#,
+        owner_id,
+        name,
+        exclude_category_id
+    )
+    .fetch_one(conn)
+    .await?;
+    if count > 0 {
+        return Err(AppError::public("分组名称已存在"));
+    }
+    Ok(())
+}
+
+/// 创建好友分组
+pub async fn create_category(owner_id: &str, name: &str) -> AppResult<ImFriendCategory> {
+    ImFriendCategory::validate_name(name).map_err(AppError::public)?;
+    let name = name.trim();
+
+    let conn = db::pool();
+    let count = sqlx::query_scalar!(
+        r#"
+            SELECT COUNT(*) as "count!: i64" FROM im_friend_category
+            WHERE owner_id = $1 AND del_flag = 1
+        "#,
+        owner_id
+    )
+    .fetch_one(conn)
+    .await?;
+    if count >= MAX_CATEGORY_COUNT {
+        return Err(AppError::public(f!(
+            "好友分组数量不能超过{}个",
+            MAX_CATEGORY_COUNT
+        )));
+    }
+    ensure_name_available(owner_id, name, None).await?;
+
+    let now = OffsetDateTime::now_utc();
+    let mut tx = conn.begin().await?;
+    let sequence = im_friendship_service::next_sequence(&mut tx, owner_id).await?;
+    let category = sqlx::query_as!(
+        ImFriendCategory,
+        r#"
+            INSERT INTO im_friend_category
+            (category_id, owner_id, name, sequence, create_time, update_time, del_flag, version)
+            VALUES ($1, $2, $3, $4, $5, $5, 1, 1)
+            RETURNING category_id, owner_id, name, sequence, create_time, update_time, del_flag, version
+        "#,
+        Ulid::new().to_string(),
+        owner_id,
+        name,
+        sequence,
+        now
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(category)
+}
+
+/// 重命名好友分组
+pub async fn rename_category(
+    owner_id: &str,
+    category_id: &str,
+    name: &str,
+) -> AppResult<ImFriendCategory> {
+    ImFriendCategory::validate_name(name).map_err(AppError::public)?;
+    let name = name.trim();
+
+    get_category(owner_id, category_id).await?;
+    ensure_name_available(owner_id, name, Some(category_id)).await?;
+
+    let conn = db::pool();
+    let now = OffsetDateTime::now_utc();
+    let mut tx = conn.begin().await?;
+    let sequence = im_friendship_service::next_sequence(&mut tx, owner_id).await?;
+
+    let category = sqlx::query_as!(
+        ImFriendCategory,
+        r#"
+            UPDATE im_friend_category
+            SET name = $1, sequence = $2, update_time = $3, version = version + 1
+            WHERE category_id = $4 AND owner_id = $5
+            RETURNING category_id, owner_id, name, sequence, create_time, update_time, del_flag, version
+        "#,
+        name,
+        sequence,
+        now,
+        category_id,
+        owner_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    touch_category_friendships(&mut tx, owner_id, category_id, now).await?;
+
+    tx.commit().await?;
+    Ok(category)
+}
+
+/// 删除好友分组（分组内的好友关系保留）
+pub async fn delete_category(owner_id: &str, category_id: &str) -> AppResult<()> {
+    get_category(owner_id, category_id).await?;
+
+    let conn = db::pool();
+    let now = OffsetDateTime::now_utc();
+    let mut tx = conn.begin().await?;
+    let sequence = im_friendship_service::next_sequence(&mut tx, owner_id).await?;
+
+    sqlx::query!(
+        r#"
+            UPDATE im_friend_category
+            SET del_flag = 0, sequence = $1, update_time = $2, version = version + 1
+            WHERE category_id = $3 AND owner_id = $4
+        "#,
+        sequence,
+        now,
+        category_id,
+        owner_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // 先更新分组内好友的序列号，再删除分组成员
+    touch_category_friendships(&mut tx, owner_id, category_id, now).await?;
+
+    sqlx::query!(
+        r#"DELETE FROM im_friend_category_member WHERE category_id = $1"#,
+        category_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 设置好友所属的分组（覆盖原有分组，传空列表表示移出所有分组）
+pub async fn set_friend_categories(
+    owner_id: &str,
+    to_id: &str,
+    category_ids: &[String],
+) -> AppResult<()> {
+    let conn = db::pool();
+
+    let friendship_exists = sqlx::query_scalar!(
+        r#"
+            SELECT COUNT(*) as "count!: i64" FROM im_friendship
+            WHERE owner_id = $1 AND to_id = $2 AND (del_flag IS NULL OR del_flag = 1)
+        "#,
+        owner_id,
+        to_id
+    )
+    .fetch_one(conn)
+    .await?;
+    if friendship_exists == 0 {
+        return Err(AppError::not_found("好友关系不存在"));
+    }
+
+    let mut category_ids = category_ids.to_vec();
+    category_ids.sort();
+    category_ids.dedup();
+
+    let valid_count = sqlx::query_scalar!(
+        r#"
+            SELECT COUNT(*) as "count!: i64" FROM im_friend_category
+            WHERE owner_id = $1 AND category_id = ANY($2) AND del_flag = 1
+        "#,
+        owner_id,
+        &category_ids
+    )
+    .fetch_one(conn)
+    .await?;
+    if valid_count != category_ids.len() as i64 {
+        return Err(AppError::not_found("好友分组不存在"));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let mut tx = conn.begin().await?;
+
+    sqlx::query!(
+        r#"DELETE FROM im_friend_category_member WHERE owner_id = $1 AND to_id = $2"#,
+        owner_id,
+        to_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    for category_id in &category_ids {
+        sqlx::query!(
+            r#"
+                INSERT INTO im_friend_category_member (category_id, owner_id, to_id, create_time)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            category_id,
+            owner_id,
+            to_id,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // 更新好友关系序列号，其他设备据此同步分组变更
+    let sequence = im_friendship_service::next_sequence(&mut tx, owner_id).await?;
+    sqlx::query!(
+        r#"
+            UPDATE im_friendship
+            SET sequence = $1, update_time = $2, version = version + 1
+            WHERE owner_id = $3 AND to_id = $4
+        "#,
+        sequence,
+        now,
+        owner_id,
+        to_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 增量同步好友分组：返回序列号大于 since 的分组（包含已删除的分组）
+pub async fn sync_categories(owner_id: &str, since: i64) -> AppResult<Vec<ImFriendCategory>> {
+    let conn = db::pool();
+    let categories = sqlx::query_as!(
+        ImFriendCategory,
+        r#"
+            SELECT category_id, owner_id, name, sequence, create_time, update_time, del_flag, version
+            FROM im_friend_category
+            WHERE owner_id = $1 AND sequence > $2
+            ORDER BY sequence ASC
+        "#,
+        owner_id,
+        since
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(categories)
+}
+
+/// 获取好友所属分组：to_id -> category_ids
+pub async fn get_friend_category_map(owner_id: &str) -> AppResult<HashMap<String, Vec<String>>> {
+    let conn = db::pool();
+    let rows = sqlx::query!(
+        r#"
+            SELECT m.to_id, m.category_id
+            FROM im_friend_category_member m
+            JOIN im_friend_category c ON c.category_id = m.category_id AND c.del_flag = 1
+            WHERE m.owner_id = $1
+            ORDER BY m.create_time ASC
+        "#,
+        owner_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        map.entry(row.to_id).or_default().push(row.category_id);
+    }
+    Ok(map)
+}
+
+/// 分组变更时更新组内好友关系的序列号
+///
+/// 每条好友关系单独分配序列号，避免同步分页时相同序列号的记录被分到两页而丢失
+async fn touch_category_friendships(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    owner_id: &str,
+    category_id: &str,
+    now: OffsetDateTime,
+) -> AppResult<()> {
+    let to_ids = sqlx::query_scalar!(
+        r#"
+            SELECT to_id FROM im_friend_category_member
+            WHERE category_id = $1 AND owner_id = $2
+            ORDER BY to_id
+        "#,
+        category_id,
+        owner_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    for to_id in to_ids {
+        let sequence = im_friendship_service::next_sequence(tx, owner_id).await?;
+        sqlx::query!(
+            r#"
+                UPDATE im_friendship
+                SET sequence = $1, update_time = $2, version = version + 1
+                WHERE owner_id = $3 AND to_id = $4
+            "#,
+            sequence,
+            now,
+            owner_id,
+            to_id
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
@@ -1,8 +1,14 @@
+use crate::service::im_user_service;
 use crate::{db, prelude::*};
 
-use crate::models::{ImFriendship, ImFriendshipRequest};
+use crate::models::im_friendship::{
+    APPROVE_STATUS_APPROVED, APPROVE_STATUS_EXPIRED, APPROVE_STATUS_PENDING,
+    APPROVE_STATUS_REJECTED, APPROVE_STATUS_WITHDRAWN,
+};
+use crate::models::im_user::{FRIEND_ALLOW_NO_VERIFY, FRIEND_ALLOW_NOBODY, FRIEND_ALLOW_VERIFY};
+use crate::models::{FriendRecommendation, ImFriendship, ImFriendshipRequest};
 
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 
 pub async fn is_friend(owner_id: &str, to_id: &str) -> AppResult<bool> {
     let conn = db::pool();
@@ -291,10 +297,30 @@ pub async fn add_friend(
         return Err(AppError::public("已经是好友"));
     }
 
-    let timestamp = OffsetDateTime::now_utc();
-
     let mut tx = conn.begin().await?;
+    insert_friendship_pair(
+        &mut tx,
+        &owner_id,
+        &to_id,
+        add_source.as_deref(),
+        remark.as_deref(),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
 
+/// 在事务内写入双向好友关系，两侧各自分配序列号；已有记录保留原有的拉黑状态
+async fn insert_friendship_pair(
+    conn: &mut sqlx::PgConnection,
+    owner_id: &str,
+    to_id: &str,
+    add_source: Option<&str>,
+    remark: Option<&str>,
+) -> AppResult<()> {
+    let timestamp = OffsetDateTime::now_utc();
+
+    let owner_sequence = next_sequence(conn, owner_id).await?;
     sqlx::query!(
         r#"
             INSERT INTO im_friendship
@@ -302,21 +328,22 @@ pub async fn add_friend(
             VALUES ($1, $2, $3, 1, 1, $4, $4, $5, $6, 1)
             ON CONFLICT (owner_id, to_id) DO UPDATE SET
             del_flag = 1,
-            black = 1,
             remark = EXCLUDED.remark,
             update_time = $4,
+            sequence = EXCLUDED.sequence,
             version = im_friendship.version + 1
          "#,
          owner_id,
          to_id,
          remark,
          timestamp,
-         timestamp.unix_timestamp() * 1000,
+         owner_sequence,
          add_source,
     )
-    .execute(&mut *tx)
+    .execute(&mut *conn)
     .await?;
 
+    let to_sequence = next_sequence(conn, to_id).await?;
     sqlx::query!(
         r#"
             INSERT INTO im_friendship
@@ -324,22 +351,20 @@ pub async fn add_friend(
             VALUES ($1, $2, $3, 1, 1, $4, $4, $5, $6, 1)
             ON CONFLICT (owner_id, to_id) DO UPDATE SET
             del_flag = 1,
-            black = 1,
             remark = EXCLUDED.remark,
             update_time = $4,
+            sequence = EXCLUDED.sequence,
             version = im_friendship.version + 1
         "#,
         to_id,
         owner_id,
         remark,
         timestamp,
-        timestamp.unix_timestamp() * 1000,
+        to_sequence,
         add_source,
     )
-    .execute(&mut *tx)
+    .execute(&mut *conn)
     .await?;
-
-    tx.commit().await?;
     Ok(())
 }
 
@@ -365,28 +390,36 @@ pub async fn remove_friend(owner_id: &str, to_id: &str) -> AppResult<()> {
         return Err(AppError::not_found("好友不存在"));
     }
 
-    // 软删除双向关系
-    let result = sqlx::query!(
-        r#"
-            UPDATE im_friendship
-            SET del_flag = 0, update_time = $1, version = version + 1
-            WHERE ((owner_id = $2 AND to_id = $3) OR (owner_id = $3 AND to_id = $2))
-            AND (del_flag IS NULL OR del_flag = 1)
-         "#,
-        now,
-        owner_id,
-        to_id
-    )
-    .execute(conn)
-    .await
-    .inspect_err(|e| {
-        warn!(
-            "删除好友关系失败: owner_id={}, to_id={}, error={:?}",
-            owner_id, to_id, e
-        );
-    })?;
+    // 软删除双向关系，两侧各自更新序列号
+    let mut tx = conn.begin().await?;
+    let mut rows_affected = 0;
+    for (from, to) in [(owner_id, to_id), (to_id, owner_id)] {
+        let sequence = next_sequence(&mut tx, from).await?;
+        let result = sqlx::query!(
+            r#"
+                UPDATE im_friendship
+                SET del_flag = 0, sequence = $1, update_time = $2, version = version + 1
+                WHERE owner_id = $3 AND to_id = $4
+                AND (del_flag IS NULL OR del_flag = 1)
+             "#,
+            sequence,
+            now,
+            from,
+            to
+        )
+        .execute(&mut *tx)
+        .await
+        .inspect_err(|e| {
+            warn!(
+                "删除好友关系失败: owner_id={}, to_id={}, error={:?}",
+                owner_id, to_id, e
+            );
+        })?;
+        rows_affected += result.rows_affected();
+    }
+    tx.commit().await?;
 
-    if result.rows_affected() == 0 {
+    if rows_affected == 0 {
         warn!(
             "删除好友关系时没有更新任何记录: owner_id={}, to_id={}",
             owner_id, to_id
@@ -395,9 +428,7 @@ pub async fn remove_friend(owner_id: &str, to_id: &str) -> AppResult<()> {
     }
     info!(
         "成功删除好友关系: owner_id={}, to_id={}, rows_affected={}",
-        owner_id,
-        to_id,
-        result.rows_affected()
+        owner_id, to_id, rows_affected
     );
     Ok(())
 }
@@ -405,72 +436,234 @@ pub async fn remove_friend(owner_id: &str, to_id: &str) -> AppResult<()> {
 pub async fn update_remark(owner_id: &str, to_id: &str, remark: Option<String>) -> AppResult<()> {
     let conn = db::pool();
     let now = OffsetDateTime::now_utc();
+    let mut tx = conn.begin().await?;
+    let sequence = next_sequence(&mut tx, owner_id).await?;
     sqlx::query!(
         r#"
             UPDATE im_friendship
-            SET remark = $1, update_time = $2, version = version + 1
-            WHERE owner_id = $3 AND to_id = $4
+            SET remark = $1, sequence = $2, update_time = $3, version = version + 1
+            WHERE owner_id = $4 AND to_id = $5
             AND (del_flag IS NULL OR del_flag = 1)
         "#,
         remark,
+        sequence,
         now,
         owner_id,
         to_id
     )
-    .execute(conn)
+    .execute(&mut *tx)
     .await?;
+    tx.commit().await?;
     Ok(())
 }
 
-/// 拉黑/取消拉黑好友（切换状态）
+/// 拉黑好友
 pub async fn black_friend(owner_id: &str, to_id: &str) -> AppResult<()> {
+    set_black(owner_id, to_id, 2).await
+}
+
+/// 取消拉黑
+pub async fn unblock_friend(owner_id: &str, to_id: &str) -> AppResult<()> {
+    set_black(owner_id, to_id, 1).await
+}
+
+async fn set_black(owner_id: &str, to_id: &str, black: i32) -> AppResult<()> {
     let conn = db::pool();
     let now = OffsetDateTime::now_utc();
 
-    // 先查询当前状态
-    let current_black = sqlx::query_scalar!(
+    let mut tx = conn.begin().await?;
+    let sequence = next_sequence(&mut tx, owner_id).await?;
+    let result = sqlx::query!(
         r#"
-            SELECT black FROM im_friendship WHERE owner_id = $1 AND to_id = $2
-        "#,
+            UPDATE im_friendship
+            SET black = $1, sequence = $2, black_sequence = $3, update_time = $4,
+            version = version + 1
+            WHERE owner_id = $5 AND to_id = $6
+         "#,
+        black,
+        sequence,
+        if black == 2 { Some(sequence) } else { None },
+        now,
         owner_id,
         to_id
     )
-    .fetch_optional(conn)
-    .await?
-    .flatten();
+    .execute(&mut *tx)
+    .await?;
 
-    let new_black = if current_black == Some(2) {
-        // 如果已经拉黑，则取消拉黑
-        1
-    } else {
-        // 否则拉黑
-        2
-    };
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("好友关系不存在"));
+    }
+    tx.commit().await?;
+    Ok(())
+}
 
-    sqlx::query!(
+/// owner_id 是否拉黑了 to_id
+pub async fn is_blocked(owner_id: &str, to_id: &str) -> AppResult<bool> {
+    let conn = db::pool();
+    let count = sqlx::query_scalar!(
         r#"
-            UPDATE im_friendship
-            SET black = $1, black_sequence = $2, update_time = $3, version = version + 1
-            WHERE owner_id = $4 AND to_id = $5
-         "#,
-        new_black,
-        if new_black == 2 {
-            Some(OffsetDateTime::now_utc().unix_timestamp() * 1000)
-        } else {
-            None
-        },
-        now,
+            SELECT COUNT(*) as "count!: i64" FROM im_friendship
+            WHERE owner_id = $1 AND to_id = $2 AND black = 2
+        "#,
         owner_id,
         to_id
     )
-    .execute(conn)
+    .fetch_one(conn)
     .await?;
+    Ok(count > 0)
+}
 
-    Ok(())
+/// 获取黑名单
+pub async fn get_black_list(owner_id: &str) -> AppResult<Vec<ImFriendship>> {
+    let conn = db::pool();
+    let friends = sqlx::query_as!(
+        ImFriendship,
+        r#"
+            SELECT owner_id, to_id, remark, del_flag, black, create_time, update_time,
+            sequence, black_sequence, add_source, extra, version
+            FROM im_friendship
+            WHERE owner_id = $1 AND black = 2
+            ORDER BY black_sequence DESC
+        "#,
+        owner_id
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(friends)
+}
+
+/// 获取 owner_id 的下一个好友关系序列号
+///
+/// 首次使用时从该用户已有的最大序列号开始，保证序列号按用户单调递增
+pub async fn next_sequence(conn: &mut sqlx::PgConnection, owner_id: &str) -> AppResult<i64> {
+    let sequence = sqlx::query_scalar!(
+        r#"
+            INSERT INTO im_friendship_sequence (owner_id, sequence)
+            VALUES ($1::varchar, COALESCE((SELECT MAX(sequence) FROM im_friendship WHERE owner_id = $1::varchar), 0) + 1)
+            ON CONFLICT (owner_id) DO UPDATE SET
+            sequence = im_friendship_sequence.sequence + 1
+            RETURNING sequence
+        "#,
+        owner_id
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok(sequence)
+}
+
+/// 增量同步好友关系：返回序列号大于 since 的变更（包含已删除、已拉黑的记录）
+///
+/// 按序列号升序返回，最多 limit 条
+pub async fn sync_friends(owner_id: &str, since: i64, limit: i64) -> AppResult<Vec<ImFriendship>> {
+    let conn = db::pool();
+    let friends = sqlx::query_as!(
+        ImFriendship,
+        r#"
+            SELECT owner_id, to_id, remark, del_flag, black, create_time, update_time,
+            sequence, black_sequence, add_source, extra, version
+            FROM im_friendship
+            WHERE owner_id = $1 AND sequence > $2
+            ORDER BY sequence ASC
+            LIMIT $3
+        "#,
+        owner_id,
+        since,
+        limit
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(friends)
+}
+
+/// 好友推荐：按共同好友数、共同群组数排序
+///
+/// 排除已是好友、任一方拉黑、对方禁止添加好友以及存在待处理申请的用户
+pub async fn get_recommendations(
+    owner_id: &str,
+    limit: i64,
+) -> AppResult<Vec<FriendRecommendation>> {
+    let conn = db::pool();
+    let recommendations = sqlx::query_as!(
+        FriendRecommendation,
+        r#"
+            WITH my_friends AS (
+                SELECT to_id FROM im_friendship
+                WHERE owner_id = $1
+                AND (del_flag IS NULL OR del_flag = 1)
+                AND (black IS NULL OR black = 1)
+            ),
+            candidates AS (
+                SELECT f.to_id AS user_id, 1 AS mutual_friend, 0 AS shared_group
+                FROM im_friendship f
+                JOIN my_friends m ON f.owner_id = m.to_id
+                WHERE (f.del_flag IS NULL OR f.del_flag = 1)
+                AND (f.black IS NULL OR f.black = 1)
+                UNION ALL
+                SELECT g.user_id, 0 AS mutual_friend, 1 AS shared_group
+                FROM (
+                    SELECT DISTINCT other.member_id AS user_id, other.group_id
+                    FROM im_group_member me
+                    JOIN im_group_member other ON other.group_id = me.group_id
+                    WHERE me.member_id = $1 AND me.del_flag = 1 AND other.del_flag = 1
+                ) g
+            )
+            SELECT c.user_id as "user_id!",
+                SUM(c.mutual_friend)::bigint as "mutual_friend_count!",
+                SUM(c.shared_group)::bigint as "shared_group_count!"
+            FROM candidates c
+            WHERE c.user_id <> $1
+            AND NOT EXISTS (
+                SELECT 1 FROM im_friendship f
+                WHERE f.owner_id = $1 AND f.to_id = c.user_id
+                AND (f.del_flag IS NULL OR f.del_flag = 1)
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM im_friendship f
+                WHERE ((f.owner_id = $1 AND f.to_id = c.user_id)
+                    OR (f.owner_id = c.user_id AND f.to_id = $1))
+                AND f.black = 2
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM im_user_data d
+                WHERE d.user_id = c.user_id
+                AND (d.disable_add_friend = 1 OR d.friend_allow_type = 3 OR d.forbidden_flag = 1)
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM im_friendship_request r
+                WHERE ((r.from_id = $1 AND r.to_id = c.user_id)
+                    OR (r.from_id = c.user_id AND r.to_id = $1))
+                AND r.approve_status = $3
+                AND (r.del_flag IS NULL OR r.del_flag = 1)
+            )
+            GROUP BY c.user_id
+            ORDER BY 2 DESC, 3 DESC, c.user_id ASC
+            LIMIT $2
+        "#,
+        owner_id,
+        limit,
+        APPROVE_STATUS_PENDING
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(recommendations)
+}
+
+/// 好友申请的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FriendshipRequestOutcome {
+    /// 对方需要验证，申请待处理
+    Pending,
+    /// 对方无需验证，已直接成为好友
+    Accepted,
 }
 
 /// 创建好友请求
-pub async fn create_friendship_request(request: ImFriendshipRequest) -> AppResult<()> {
+///
+/// 根据对方的 friend_allow_type 决定是否需要验证，无需验证时在同一事务内建立好友关系
+pub async fn create_friendship_request(
+    request: ImFriendshipRequest,
+    expire_days: i64,
+) -> AppResult<FriendshipRequestOutcome> {
     let conn = db::pool();
 
     request.validate().map_err(|e| {
@@ -478,15 +671,31 @@ pub async fn create_friendship_request(request: ImFriendshipRequest) -> AppResul
         AppError::public(e)
     })?;
 
+    im_user_service::ensure_can_add_friend(&request.from_id, &request.to_id).await?;
+    if is_blocked(&request.to_id, &request.from_id).await? {
+        return Err(AppError::public("对方已将你拉黑，无法添加好友"));
+    }
+
+    let friend_allow_type = match im_user_service::get_user_data(&request.to_id).await {
+        Ok(data) => data.friend_allow_type,
+        Err(AppError::NotFound(_)) => FRIEND_ALLOW_VERIFY,
+        Err(e) => return Err(e),
+    };
+    if friend_allow_type == FRIEND_ALLOW_NOBODY {
+        return Err(AppError::public("对方设置了不允许任何人添加好友"));
+    }
+
     if is_friend(&request.from_id, &request.to_id).await? {
         return Err(AppError::public("不能重复添加好友"));
     }
 
-    // 检查是否已经有待处理的好友请求（只检查待处理的，已拒绝的可以重新发送）
-    let existing_requests = get_friendship_requests(&request.to_id, Some(0)).await?;
+    // 检查是否已经有待处理的好友请求（只检查待处理的，已拒绝和已过期的可以重新发送）
+    expire_pending_requests_between(&request.from_id, &request.to_id, expire_days).await?;
+    let existing_requests =
+        get_friendship_requests(&request.to_id, Some(APPROVE_STATUS_PENDING)).await?;
     if existing_requests
         .iter()
-        .any(|r| r.from_id == request.from_id && r.approve_status == Some(0))
+        .any(|r| r.from_id == request.from_id && r.approve_status == Some(APPROVE_STATUS_PENDING))
     {
         warn!(
             "已经存在待处理的好友请求: from_id={}, to_id={}",
@@ -500,27 +709,37 @@ pub async fn create_friendship_request(request: ImFriendshipRequest) -> AppResul
     sqlx::query!(
         r#"
             DELETE FROM im_friendship_request
-            WHERE from_id = $1 AND to_id = $2 AND approve_status = 2
+            WHERE from_id = $1 AND to_id = $2 AND approve_status = $3
         "#,
         request.from_id,
-        request.to_id
+        request.to_id,
+        APPROVE_STATUS_REJECTED
     )
     .execute(conn)
     .await
     .ok(); // 忽略删除错误（可能没有旧记录）
 
+    // 对方无需验证时直接建立双向好友关系，申请记录为已同意
+    let outcome = if friend_allow_type == FRIEND_ALLOW_NO_VERIFY {
+        FriendshipRequestOutcome::Accepted
+    } else {
+        FriendshipRequestOutcome::Pending
+    };
+    let approve_status = match outcome {
+        FriendshipRequestOutcome::Accepted => APPROVE_STATUS_APPROVED,
+        FriendshipRequestOutcome::Pending => APPROVE_STATUS_PENDING,
+    };
+
     let timestamp = OffsetDateTime::now_utc();
 
+    let mut tx = conn.begin().await?;
     let result = sqlx::query!(
         r#"
             INSERT INTO im_friendship_request
             (id, from_id, to_id, remark, read_status, add_source, message, approve_status,
             create_time, update_time, sequence, del_flag, version)
-            VALUES ($1, $2, $3, $4, 0, $5, $6, 0, $7, $8, $9, 1, 1)
-            ON CONFLICT (id) DO UPDATE SET
-            approve_status = 0,
-            update_time = $8,
-            version = im_friendship_request.version + 1
+            VALUES ($1, $2, $3, $4, 0, $5, $6, $10, $7, $8, $9, 1, 1)
+            ON CONFLICT (id) DO NOTHING
         "#,
         request.id,
         request.from_id,
@@ -531,12 +750,34 @@ pub async fn create_friendship_request(request: ImFriendshipRequest) -> AppResul
         timestamp,
         timestamp,
         timestamp.unix_timestamp() * 1000,
+        approve_status,
     )
-    .execute(conn)
+    .execute(&mut *tx)
     .await;
 
     match result {
-        Ok(_) => Ok(()),
+        // 申请ID已存在时不覆盖原申请的状态
+        Ok(result) if result.rows_affected() == 0 => {
+            warn!(
+                "好友请求ID已存在: request_id={}, from_id={}, to_id={}",
+                request.id, request.from_id, request.to_id
+            );
+            Err(AppError::public("好友请求已存在"))
+        }
+        Ok(_) => {
+            if outcome == FriendshipRequestOutcome::Accepted {
+                insert_friendship_pair(
+                    &mut tx,
+                    &request.from_id,
+                    &request.to_id,
+                    request.add_source.as_deref(),
+                    request.remark.as_deref(),
+                )
+                .await?;
+            }
+            tx.commit().await?;
+            Ok(outcome)
+        }
         Err(e) => {
             error!(
                 "创建好友请求数据库错误: request_id={}, from_id={}, to_id={}, error={:?}",
@@ -616,29 +857,172 @@ pub async fn get_friendship_requests(
     Ok(requests)
 }
 
-/// 处理好友请求（同意或拒绝）
-pub async fn handle_friendship_request(request_id: &str, approve_status: i32) -> AppResult<()> {
+/// 获取自己发出的好友请求列表
+pub async fn get_sent_friendship_requests(
+    from_id: &str,
+    approve_status: Option<i32>,
+) -> AppResult<Vec<ImFriendshipRequest>> {
+    let conn = db::pool();
+    let requests = sqlx::query_as!(
+        ImFriendshipRequest,
+        r#"
+            SELECT id, from_id, to_id, remark, read_status, add_source, message,
+                approve_status, create_time, update_time, sequence, del_flag, version
+            FROM im_friendship_request
+            WHERE from_id = $1
+            AND (del_flag IS NULL OR del_flag = 1)
+            AND ($2::integer IS NULL OR approve_status = $2)
+            ORDER BY create_time DESC
+        "#,
+        from_id,
+        approve_status
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(requests)
+}
+
+/// 处理好友请求（同意或拒绝），只能处理发给自己且未过期的待处理请求
+pub async fn handle_friendship_request(
+    to_id: &str,
+    request_id: &str,
+    approve_status: i32,
+    expire_days: i64,
+) -> AppResult<ImFriendshipRequest> {
+    if approve_status != APPROVE_STATUS_APPROVED && approve_status != APPROVE_STATUS_REJECTED {
+        return Err(AppError::public("无效的审批状态"));
+    }
+
     let conn = db::pool();
     let timestamp = OffsetDateTime::now_utc();
+    let mut tx = conn.begin().await?;
     let request = sqlx::query_as!(
         ImFriendshipRequest,
         r#"
             UPDATE im_friendship_request
-            SET approve_status = $1, update_time = $2, version = version + 1
-            WHERE id = $3
+            SET approve_status = $1, read_status = 1, update_time = $2, version = version + 1
+            WHERE id = $3 AND to_id = $4 AND approve_status = $5
+            AND create_time >= $6
             RETURNING *
          "#,
         approve_status,
         timestamp,
-        request_id
+        request_id,
+        to_id,
+        APPROVE_STATUS_PENDING,
+        timestamp - Duration::days(expire_days)
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::not_found("好友请求不存在或已失效"))?;
+
+    // 同意时在同一事务内建立好友关系，失败时申请保持待处理
+    if approve_status == APPROVE_STATUS_APPROVED {
+        insert_friendship_pair(
+            &mut tx,
+            &request.from_id,
+            &request.to_id,
+            request.add_source.as_deref(),
+            request.remark.as_deref(),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(request)
+}
+
+/// 撤回自己发出的待处理好友请求
+pub async fn withdraw_friendship_request(
+    from_id: &str,
+    request_id: &str,
+) -> AppResult<ImFriendshipRequest> {
+    let conn = db::pool();
+    sqlx::query_as!(
+        ImFriendshipRequest,
+        r#"
+            UPDATE im_friendship_request
+            SET approve_status = $1, update_time = $2, version = version + 1
+            WHERE id = $3 AND from_id = $4 AND approve_status = $5
+            RETURNING *
+         "#,
+        APPROVE_STATUS_WITHDRAWN,
+        OffsetDateTime::now_utc(),
+        request_id,
+        from_id,
+        APPROVE_STATUS_PENDING
     )
     .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::not_found("好友请求不存在或已处理"))
+}
+
+/// 批量标记收到的好友请求为已读，request_ids 为空时标记全部
+pub async fn mark_friendship_requests_read(
+    to_id: &str,
+    request_ids: Option<Vec<String>>,
+) -> AppResult<u64> {
+    let conn = db::pool();
+    let result = sqlx::query!(
+        r#"
+            UPDATE im_friendship_request
+            SET read_status = 1, update_time = $1
+            WHERE to_id = $2
+            AND (read_status IS NULL OR read_status = 0)
+            AND ($3::varchar[] IS NULL OR id = ANY($3))
+        "#,
+        OffsetDateTime::now_utc(),
+        to_id,
+        request_ids.as_deref()
+    )
+    .execute(conn)
     .await?;
+    Ok(result.rows_affected())
+}
 
-    if approve_status == 1
-        && let Some(req) = request {
-            add_friend(req.from_id, req.to_id, req.add_source, req.remark).await?;
-        }
+/// 将两个用户之间超过有效期仍未处理的好友请求标记为已过期（不等待定时清理）
+pub async fn expire_pending_requests_between(
+    user_a: &str,
+    user_b: &str,
+    expire_days: i64,
+) -> AppResult<u64> {
+    let conn = db::pool();
+    let now = OffsetDateTime::now_utc();
+    let result = sqlx::query!(
+        r#"
+            UPDATE im_friendship_request
+            SET approve_status = $1, update_time = $2, version = version + 1
+            WHERE ((from_id = $3 AND to_id = $4) OR (from_id = $4 AND to_id = $3))
+            AND approve_status = $5 AND create_time < $6
+        "#,
+        APPROVE_STATUS_EXPIRED,
+        now,
+        user_a,
+        user_b,
+        APPROVE_STATUS_PENDING,
+        now - Duration::days(expire_days)
+    )
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected())
+}
 
-    Ok(())
+/// 将超过有效期仍未处理的好友请求标记为已过期，返回过期的数量
+pub async fn expire_friendship_requests(expire_days: i64) -> AppResult<u64> {
+    let conn = db::pool();
+    let now = OffsetDateTime::now_utc();
+    let result = sqlx::query!(
+        r#"
+            UPDATE im_friendship_request
+            SET approve_status = $1, update_time = $2, version = version + 1
+            WHERE approve_status = $3 AND create_time < $4
+        "#,
+        APPROVE_STATUS_EXPIRED,
+        now,
+        APPROVE_STATUS_PENDING,
+        now - Duration::days(expire_days)
+    )
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected())
 }
@@ -0,0 +1,290 @@
+use crate::db;
+use crate::dto::{GroupAnnouncementAckStatus, GroupAnnouncementResp};
+use crate::models::{GroupRole, ImGroupAnnouncement};
+use crate::prelude::*;
+use crate::service::im_group_service;
+use time::OffsetDateTime;
+use ulid::Ulid;
+
+/// im_group.notification 的长度限制（VARCHAR(1000)）
+const NOTIFICATION_MAX_CHARS: usize = 1000;
+
+async fn get_announcement(group_id: &str, announcement_id: &str) -> AppResult<ImGroupAnnouncement> {
+    let conn = db::pool();
+    sqlx::query_as!(
+        ImGroupAnnouncement,
+        r#"
+        SELECT announcement_id, group_id, author_id, content, pinned, del_flag, create_time, update_time
+         FROM im_group_announcement
+         WHERE announcement_id = $1 AND group_id = $2 AND del_flag = 1
+         "#,
+        announcement_id,
+        group_id
+    )
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::not_found("群公告不存在"))
+}
+
+/// 发布群公告（只有群主和管理员可以发布）
+///
+/// 同时将 im_group.notification 更新为最新公告，兼容只读取该字段的客户端
+pub async fn create_announcement(
+    group_id: &str,
+    operator_id: &str,
+    content: &str,
+    pinned: bool,
+) -> AppResult<ImGroupAnnouncement> {
+    ImGroupAnnouncement::validate_content(content).map_err(AppError::public)?;
+    let content = content.trim();
+
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+
+    let conn = db::pool();
+    let now = OffsetDateTime::now_utc();
+    let mut tx = conn.begin().await?;
+    let announcement = sqlx::query_as!(
+        ImGroupAnnouncement,
+        r#"
+        INSERT INTO im_group_announcement
+         (announcement_id, group_id, author_id, content, pinned, del_flag, create_time, update_time)
+         VALUES ($1, $2, $3, $4, $5, 1, $6, $6)
+         RETURNING announcement_id, group_id, author_id, content, pinned, del_flag, create_time, update_time
+         "#,
+        Ulid::new().to_string(),
+        group_id,
+        operator_id,
+        content,
+        pinned as i16,
+        now
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // 发布人视为已确认
+    sqlx::query!(
+        r#"
+        INSERT INTO im_group_announcement_ack (announcement_id, member_id, ack_time)
+         VALUES ($1, $2, $3)
+         "#,
+        announcement.announcement_id,
+        operator_id,
+        now
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"UPDATE im_group SET notification = $1, update_time = $2 WHERE group_id = $3"#,
+        content
+            .chars()
+            .take(NOTIFICATION_MAX_CHARS)
+            .collect::<String>(),
+        now,
+        group_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(announcement)
+}
+
+/// 获取群公告列表（群成员可查看），置顶公告在前，其余按发布时间倒序
+pub async fn get_announcements(
+    group_id: &str,
+    user_id: &str,
+) -> AppResult<Vec<GroupAnnouncementResp>> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, user_id, GroupRole::Member).await?;
+
+    let conn = db::pool();
+    let announcements = sqlx::query_as!(
+        GroupAnnouncementResp,
+        r#"
+        SELECT a.announcement_id, a.group_id, a.author_id, a.content, a.pinned,
+                a.create_time, a.update_time,
+                EXISTS(
+                    SELECT 1 FROM im_group_announcement_ack k
+                    WHERE k.announcement_id = a.announcement_id AND k.member_id = $2
+                ) as "acknowledged!",
+                (
+                    SELECT COUNT(*) FROM im_group_announcement_ack k
+                    WHERE k.announcement_id = a.announcement_id
+                ) as "ack_count!"
+         FROM im_group_announcement a
+         WHERE a.group_id = $1 AND a.del_flag = 1
+         ORDER BY a.pinned DESC, a.create_time DESC
+         "#,
+        group_id,
+        user_id
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(announcements)
+}
+
+/// 设置公告置顶（只有群主和管理员可以设置）
+pub async fn set_announcement_pinned(
+    group_id: &str,
+    announcement_id: &str,
+    operator_id: &str,
+    pinned: bool,
+) -> AppResult<()> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+    get_announcement(group_id, announcement_id).await?;
+
+    let conn = db::pool();
+    sqlx::query!(
+        r#"
+        UPDATE im_group_announcement SET pinned = $1, update_time = $2
+         WHERE announcement_id = $3 AND group_id = $4
+         "#,
+        pinned as i16,
+        OffsetDateTime::now_utc(),
+        announcement_id,
+        group_id
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 删除群公告（只有群主和管理员可以删除）
+///
+/// 同时将 im_group.notification 回退为剩余公告中最新的一条，没有剩余公告时清空
+pub async fn delete_announcement(
+    group_id: &str,
+    announcement_id: &str,
+    operator_id: &str,
+) -> AppResult<()> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+    get_announcement(group_id, announcement_id).await?;
+
+    let conn = db::pool();
+    let now = OffsetDateTime::now_utc();
+    let mut tx = conn.begin().await?;
+    sqlx::query!(
+        r#"
+        UPDATE im_group_announcement SET del_flag = 0, update_time = $1
+         WHERE announcement_id = $2 AND group_id = $3
+         "#,
+        now,
+        announcement_id,
+        group_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE im_group SET update_time = $1, notification = (
+            SELECT LEFT(a.content, $2) FROM im_group_announcement a
+             WHERE a.group_id = $3 AND a.del_flag = 1
+             ORDER BY a.create_time DESC
+             LIMIT 1
+        )
+         WHERE group_id = $3
+         "#,
+        now,
+        NOTIFICATION_MAX_CHARS as i32,
+        group_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 群成员确认已读公告（重复确认保留首次确认时间）
+pub async fn acknowledge_announcement(
+    group_id: &str,
+    announcement_id: &str,
+    member_id: &str,
+) -> AppResult<()> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, member_id, GroupRole::Member).await?;
+    get_announcement(group_id, announcement_id).await?;
+
+    let conn = db::pool();
+    sqlx::query!(
+        r#"
+        INSERT INTO im_group_announcement_ack (announcement_id, member_id, ack_time)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (announcement_id, member_id) DO NOTHING
+         "#,
+        announcement_id,
+        member_id,
+        OffsetDateTime::now_utc()
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 获取公告的确认情况（只有群主和管理员可以查看）
+pub async fn get_announcement_ack_status(
+    group_id: &str,
+    announcement_id: &str,
+    operator_id: &str,
+) -> AppResult<GroupAnnouncementAckStatus> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+    get_announcement(group_id, announcement_id).await?;
+
+    // 确认记录按 open_id 保存，member_id 可能是用户名，按 users 解析后比较；只统计当前成员
+    let conn = db::pool();
+    let ack_count = sqlx::query_scalar!(
+        r#"
+        WITH members AS (
+            SELECT DISTINCT ON (m.member_id) COALESCE(u.open_id, m.member_id) as open_id
+             FROM im_group_member m
+             LEFT JOIN users u ON u.open_id = m.member_id OR u.name = m.member_id
+             WHERE m.group_id = $1 AND m.del_flag = 1
+             ORDER BY m.member_id, (u.open_id = m.member_id) DESC NULLS LAST
+        )
+        SELECT COUNT(*) as "count!: i64" FROM members
+         WHERE EXISTS (
+             SELECT 1 FROM im_group_announcement_ack k
+             WHERE k.announcement_id = $2 AND k.member_id = members.open_id
+         )
+         "#,
+        group_id,
+        announcement_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    let unacknowledged_member_ids = sqlx::query_scalar!(
+        r#"
+        WITH members AS (
+            SELECT DISTINCT ON (m.member_id) m.member_id, m.join_time,
+                    COALESCE(u.open_id, m.member_id) as open_id
+             FROM im_group_member m
+             LEFT JOIN users u ON u.open_id = m.member_id OR u.name = m.member_id
+             WHERE m.group_id = $1 AND m.del_flag = 1
+             ORDER BY m.member_id, (u.open_id = m.member_id) DESC NULLS LAST
+        )
+        SELECT member_id as "member_id!" FROM members
+         WHERE NOT EXISTS (
+             SELECT 1 FROM im_group_announcement_ack k
+             WHERE k.announcement_id = $2 AND k.member_id = members.open_id
+         )
+         ORDER BY join_time ASC
+         "#,
+        group_id,
+        announcement_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(GroupAnnouncementAckStatus {
+        announcement_id: announcement_id.to_string(),
+        ack_count,
+        unacknowledged_member_ids,
+    })
+}
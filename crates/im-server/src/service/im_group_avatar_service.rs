@@ -0,0 +1,309 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::config;
+use crate::db;
+use crate::prelude::*;
+use crate::service::im_group_service;
+use image::{ImageFormat, Rgb, RgbImage, imageops::FilterType};
+use std::io::Cursor;
+use time::OffsetDateTime;
+
+/// 组合头像最多使用的成员数
+const COMPOSITE_MEMBER_COUNT: i64 = 9;
+
+/// 组合头像边长（像素）
+const AVATAR_SIZE: u32 = 300;
+
+/// 成员头像之间的间隔（像素）
+const AVATAR_GAP: u32 = 6;
+
+/// 成员变更后延迟生成，合并短时间内的多次变更
+const REGENERATE_DELAY: Duration = Duration::from_secs(2);
+
+/// 组合头像背景色
+const BACKGROUND: Rgb<u8> = Rgb([221, 222, 224]);
+
+/// 没有头像的成员使用的占位色
+const PLACEHOLDER_COLORS: [Rgb<u8>; 6] = [
+    Rgb([94, 129, 172]),
+    Rgb([163, 190, 140]),
+    Rgb([208, 135, 112]),
+    Rgb([180, 142, 173]),
+    Rgb([235, 203, 139]),
+    Rgb([136, 192, 208]),
+];
+
+static PENDING: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+/// 生成的组合头像地址（不含版本参数）
+fn generated_avatar_url(group_id: &str) -> String {
+    f!("/api/v1/im/groups/{}/avatar", group_id)
+}
+
+/// 组合头像在上传目录中的路径
+pub fn generated_avatar_path(group_id: &str) -> PathBuf {
+    PathBuf::from(&config::get().upload.path)
+        .join("groups")
+        .join(f!("{}.jpg", group_id))
+}
+
+/// 群组是否使用自定义头像（非空且不是生成的组合头像）
+fn has_custom_avatar(group_id: &str, avatar: Option<&str>) -> bool {
+    avatar.map(str::trim).is_some_and(|avatar| {
+        !avatar.is_empty() && !avatar.starts_with(&generated_avatar_url(group_id))
+    })
+}
+
+/// 将头像字段解析为上传目录下的相对路径
+///
+/// 支持文件名（位于用户自己的上传目录）和上传/下载接口返回的地址，外部 URL 返回 None
+fn local_avatar_path(open_id: &str, avatar: &str) -> Option<PathBuf> {
+    let avatar = avatar.trim();
+    if avatar.is_empty() || avatar.starts_with("http://") || avatar.starts_with("https://") {
+        return None;
+    }
+    let avatar = ["/api/upload/", "/api/v1/download/"]
+        .iter()
+        .find_map(|prefix| avatar.strip_prefix(prefix))
+        .unwrap_or(avatar);
+
+    let mut segments: Vec<&str> = avatar.split('/').filter(|s| !s.is_empty()).collect();
+    // 上传接口返回的缩略图地址中 open_id 会重复一次
+    if segments.len() == 3 && segments[0] == segments[1] {
+        segments.remove(0);
+    }
+    if segments.is_empty()
+        || segments
+            .iter()
+            .any(|s| *s == ".." || *s == "." || s.contains('\\'))
+    {
+        return None;
+    }
+    if segments.len() == 1 {
+        segments.insert(0, open_id);
+    }
+    Some(segments.iter().collect())
+}
+
+/// 计算每个成员头像的位置和边长：(x, y, 边长)
+///
+/// 1 人占满，2-4 人两列，5-9 人三列；首行不满时居中
+fn grid_cells(count: usize, size: u32, gap: u32) -> Vec<(u32, u32, u32)> {
+    if count == 0 {
+        return vec![];
+    }
+    if count == 1 {
+        return vec![(gap, gap, size - gap * 2)];
+    }
+    let cols = if count <= 4 { 2 } else { 3 };
+    let rows = count.div_ceil(cols);
+    let cell = (size - gap * (cols as u32 + 1)) / cols as u32;
+    let top = (size - (cell * rows as u32 + gap * (rows as u32 - 1))) / 2;
+    let first_row = count - cols * (rows - 1);
+
+    let mut cells = Vec::with_capacity(count);
+    for row in 0..rows {
+        let in_row = if row == 0 { first_row } else { cols };
+        let row_width = cell * in_row as u32 + gap * (in_row as u32 - 1);
+        let left = (size - row_width) / 2;
+        for col in 0..in_row {
+            cells.push((
+                left + (cell + gap) * col as u32,
+                top + (cell + gap) * row as u32,
+                cell,
+            ));
+        }
+    }
+    cells
+}
+
+fn placeholder_color(open_id: &str) -> Rgb<u8> {
+    let hash = open_id.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as usize)
+    });
+    PLACEHOLDER_COLORS[hash % PLACEHOLDER_COLORS.len()]
+}
+
+/// 拼接组合头像，返回 JPEG 数据
+fn render_composite(tiles: Vec<(String, Option<Vec<u8>>)>) -> Result<Vec<u8>, String> {
+    let mut canvas = RgbImage::from_pixel(AVATAR_SIZE, AVATAR_SIZE, BACKGROUND);
+    for ((open_id, data), (x, y, cell)) in
+        tiles
+            .iter()
+            .zip(grid_cells(tiles.len(), AVATAR_SIZE, AVATAR_GAP))
+    {
+        let tile = data
+            .as_deref()
+            .and_then(|data| image::load_from_memory(data).ok())
+            .map(|img| {
+                img.resize_to_fill(cell, cell, FilterType::Triangle)
+                    .to_rgb8()
+            })
+            .unwrap_or_else(|| RgbImage::from_pixel(cell, cell, placeholder_color(open_id)));
+        image::imageops::overlay(&mut canvas, &tile, x as i64, y as i64);
+    }
+
+    let mut buffer = vec![];
+    canvas
+        .write_to(&mut Cursor::new(&mut buffer), ImageFormat::Jpeg)
+        .map_err(|e| f!("生成组合头像失败: {}", e))?;
+    Ok(buffer)
+}
+
+/// 成员变更后安排重新生成组合头像（延迟执行，同一群组合并为一次）
+pub fn schedule_regenerate(group_id: &str) {
+    let pending = PENDING.get_or_init(Default::default);
+    if !pending.lock().unwrap().insert(group_id.to_string()) {
+        return;
+    }
+    let group_id = group_id.to_string();
+    tokio::spawn(async move {
+        tokio::time::sleep(REGENERATE_DELAY).await;
+        pending.lock().unwrap().remove(&group_id);
+        if let Err(e) = regenerate_group_avatar(&group_id).await {
+            warn!(group_id = %group_id, error = ?e, "生成群组合头像失败");
+        }
+    });
+}
+
+/// 用前 N 个成员的头像生成组合头像，已设置自定义头像的群组不处理
+pub async fn regenerate_group_avatar(group_id: &str) -> AppResult<()> {
+    let group = im_group_service::get_group(group_id).await?;
+    if has_custom_avatar(group_id, group.avatar.as_deref()) {
+        return Ok(());
+    }
+
+    let conn = db::pool();
+    // member_id 可能是 open_id 或用户名，优先匹配 open_id；头像优先使用 im_user_data.avatar
+    let members = sqlx::query!(
+        r#"
+        SELECT member_id as "member_id!", open_id as "open_id?", avatar as "avatar?" FROM (
+            SELECT DISTINCT ON (m.member_id) m.member_id, m.join_time, u.open_id,
+                    COALESCE(NULLIF(d.avatar, ''), u.file_name) as avatar
+             FROM im_group_member m
+             LEFT JOIN users u ON u.open_id = m.member_id OR u.name = m.member_id
+             LEFT JOIN im_user_data d ON d.user_id = u.open_id
+             WHERE m.group_id = $1 AND m.del_flag = 1
+             ORDER BY m.member_id, (u.open_id = m.member_id) DESC NULLS LAST
+        ) members
+         ORDER BY join_time ASC NULLS LAST
+         LIMIT $2
+         "#,
+        group_id,
+        COMPOSITE_MEMBER_COUNT
+    )
+    .fetch_all(conn)
+    .await?;
+    if members.is_empty() {
+        return Ok(());
+    }
+
+    let upload_dir = PathBuf::from(&config::get().upload.path);
+    let mut tiles = Vec::with_capacity(members.len());
+    for member in members {
+        let open_id = member.open_id.unwrap_or(member.member_id);
+        let data = match member
+            .avatar
+            .as_deref()
+            .and_then(|avatar| local_avatar_path(&open_id, avatar))
+        {
+            Some(path) => tokio::fs::read(upload_dir.join(path)).await.ok(),
+            None => None,
+        };
+        tiles.push((open_id, data));
+    }
+
+    let jpeg = tokio::task::spawn_blocking(move || render_composite(tiles))
+        .await
+        .map_err(|e| AppError::internal(f!("生成组合头像失败: {}", e)))?
+        .map_err(AppError::internal)?;
+
+    let path = generated_avatar_path(group_id);
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| AppError::internal(f!("创建头像目录失败: {}", e)))?;
+    }
+    write_atomically(&path, &jpeg).await?;
+
+    // 带上版本参数，成员变更后客户端不会使用缓存的旧头像
+    let now = OffsetDateTime::now_utc();
+    let generated_url = generated_avatar_url(group_id);
+    let avatar = f!("{}?v={}", generated_url, now.unix_timestamp());
+    sqlx::query!(
+        r#"
+        UPDATE im_group SET avatar = $1, update_time = $2, version = version + 1
+         WHERE group_id = $3 AND del_flag = 1
+         AND (avatar IS NULL OR avatar = '' OR avatar LIKE $4)
+         "#,
+        avatar,
+        now,
+        group_id,
+        f!("{}%", generated_url)
+    )
+    .execute(conn)
+    .await?;
+    info!(group_id = %group_id, "群组合头像已生成");
+    Ok(())
+}
+
+/// 先写临时文件再重命名，避免读取到写了一半的头像
+async fn write_atomically(path: &Path, data: &[u8]) -> AppResult<()> {
+    let tmp_path = path.with_extension("jpg.tmp");
+    tokio::fs::write(&tmp_path, data)
+        .await
+        .map_err(|e| AppError::internal(f!("写入组合头像失败: {}", e)))?;
+    tokio::fs::rename(&tmp_path, path)
+        .await
+        .map_err(|e| AppError::internal(f!("写入组合头像失败: {}", e)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_cells() {
+        assert_eq!(grid_cells(0, 300, 6), vec![]);
+        assert_eq!(grid_cells(1, 300, 6), vec![(6, 6, 288)]);
+
+        // 3 人：两列两行，首行 1 人居中
+        let cells = grid_cells(3, 300, 6);
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0].0, (300 - cells[0].2) / 2);
+        assert_eq!(cells[1].1, cells[2].1);
+
+        for count in 1..=9 {
+            let cells = grid_cells(count, 300, 6);
+            assert_eq!(cells.len(), count);
+            assert!(
+                cells
+                    .iter()
+                    .all(|(x, y, cell)| x + cell <= 300 && y + cell <= 300)
+            );
+        }
+    }
+
+    #[test]
+    fn test_local_avatar_path() {
+        assert_eq!(
+            local_avatar_path("u1", "a.jpg"),
+            Some(PathBuf::from("u1/a.jpg"))
+        );
+        assert_eq!(
+            local_avatar_path("u1", "/api/upload/u2/u2/thumb_a.jpg"),
+            Some(PathBuf::from("u2/thumb_a.jpg"))
+        );
+        assert_eq!(
+            local_avatar_path("u1", "/api/v1/download/u2/a.jpg"),
+            Some(PathBuf::from("u2/a.jpg"))
+        );
+        assert_eq!(local_avatar_path("u1", "https://example.com/a.jpg"), None);
+        assert_eq!(local_avatar_path("u1", "../etc/passwd"), None);
+        assert_eq!(local_avatar_path("u1", " "), None);
+    }
+}
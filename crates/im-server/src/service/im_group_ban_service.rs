@@ -0,0 +1,189 @@
+use crate::db;
+use crate::models::im_group_ban::BAN_REASON_MAX_CHARS;
+use crate::models::im_group_join_request::{JOIN_STATUS_PENDING, JOIN_STATUS_REJECTED};
+use crate::models::{GroupRole, ImGroupBan};
+use crate::prelude::*;
+use crate::service::{im_group_avatar_service, im_group_service};
+use time::OffsetDateTime;
+
+/// 获取用户在群内的封禁记录（已过期的不返回），user_id 可能是 open_id 或用户名
+async fn get_active_ban(group_id: &str, user_id: &str) -> AppResult<Option<ImGroupBan>> {
+    let conn = db::pool();
+    let bans = sqlx::query_as!(
+        ImGroupBan,
+        r#"
+        SELECT group_id, member_id, operator_id, reason, expire_time, create_time
+         FROM im_group_ban
+         WHERE group_id = $1
+         AND (member_id = $2
+              OR member_id IN (SELECT name FROM users WHERE open_id = $2)
+              OR member_id IN (SELECT open_id FROM users WHERE name = $2))
+         "#,
+        group_id,
+        user_id
+    )
+    .fetch_all(conn)
+    .await?;
+    let now = OffsetDateTime::now_utc();
+    Ok(bans.into_iter().find(|ban| ban.is_active(now)))
+}
+
+/// 校验用户未被禁止加入群组，所有入群路径（邀请、申请、管理员添加）都需要调用
+pub async fn ensure_not_banned(group_id: &str, user_id: &str) -> AppResult<()> {
+    if get_active_ban(group_id, user_id).await?.is_some() {
+        warn!(
+            "用户已被禁止加入群组: group_id={}, user_id={}",
+            group_id, user_id
+        );
+        return Err(AppError::public("该用户已被禁止加入此群"));
+    }
+    Ok(())
+}
+
+/// 将用户加入群黑名单（群主可封禁管理员和成员，管理员只能封禁普通成员）
+///
+/// 用户在群内时同时移出群组，并拒绝其待处理的加群申请。返回封禁记录和是否移出了群组
+pub async fn ban_member(
+    group_id: &str,
+    operator_id: &str,
+    member_id: &str,
+    reason: Option<String>,
+    expire_time: Option<OffsetDateTime>,
+) -> AppResult<(ImGroupBan, bool)> {
+    if operator_id == member_id {
+        return Err(AppError::public("不能封禁自己"));
+    }
+    let reason = reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > BAN_REASON_MAX_CHARS)
+    {
+        return Err(AppError::public(f!(
+            "封禁原因不能超过{}个字符",
+            BAN_REASON_MAX_CHARS
+        )));
+    }
+    let now = OffsetDateTime::now_utc();
+    if expire_time.is_some_and(|expire_time| expire_time <= now) {
+        return Err(AppError::public("解除时间必须晚于当前时间"));
+    }
+
+    let group = im_group_service::get_group(group_id).await?;
+    if group.owner_id.trim() == member_id.trim() {
+        return Err(AppError::public("不能封禁群主"));
+    }
+    let is_member = im_group_service::get_group_member(group_id, member_id)
+        .await?
+        .is_some();
+    if is_member {
+        im_group_service::ensure_can_manage_member(&group, operator_id, member_id).await?;
+    } else {
+        im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+    }
+
+    // 封禁、拒绝待处理申请和移出群组在同一事务中完成，避免部分成功
+    let conn = db::pool();
+    let mut tx = conn.begin().await?;
+    let ban = sqlx::query_as!(
+        ImGroupBan,
+        r#"
+        INSERT INTO im_group_ban (group_id, member_id, operator_id, reason, expire_time, create_time)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (group_id, member_id) DO UPDATE SET
+         operator_id = EXCLUDED.operator_id,
+         reason = EXCLUDED.reason,
+         expire_time = EXCLUDED.expire_time,
+         create_time = EXCLUDED.create_time
+         RETURNING group_id, member_id, operator_id, reason, expire_time, create_time
+         "#,
+        group_id,
+        member_id,
+        operator_id,
+        reason,
+        expire_time,
+        now
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE im_group_join_request
+         SET approve_status = $1, handler_id = $2, update_time = $3, version = version + 1
+         WHERE group_id = $4 AND from_id = $5 AND approve_status = $6 AND del_flag = 1
+         "#,
+        JOIN_STATUS_REJECTED,
+        operator_id,
+        now,
+        group_id,
+        member_id,
+        JOIN_STATUS_PENDING
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if is_member {
+        sqlx::query!(
+            r#"
+            UPDATE im_group_member
+             SET del_flag = 0, leave_time = $1, update_time = $1, version = version + 1
+             WHERE group_id = $2 AND member_id = $3 AND del_flag = 1
+             "#,
+            now,
+            group_id,
+            member_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    if is_member {
+        im_group_service::invalidate_group_member_cache(group_id).await;
+        im_group_avatar_service::schedule_regenerate(group_id);
+    }
+    Ok((ban, is_member))
+}
+
+/// 将用户移出群黑名单（只有群主和管理员可以操作）
+pub async fn unban_member(group_id: &str, operator_id: &str, member_id: &str) -> AppResult<()> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+
+    let conn = db::pool();
+    let result = sqlx::query!(
+        r#"DELETE FROM im_group_ban WHERE group_id = $1 AND member_id = $2"#,
+        group_id,
+        member_id
+    )
+    .execute(conn)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("该用户不在群黑名单中"));
+    }
+    Ok(())
+}
+
+/// 获取群黑名单（只有群主和管理员可以查看），已过期的不返回
+pub async fn get_bans(group_id: &str, operator_id: &str) -> AppResult<Vec<ImGroupBan>> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+
+    let conn = db::pool();
+    let bans = sqlx::query_as!(
+        ImGroupBan,
+        r#"
+        SELECT group_id, member_id, operator_id, reason, expire_time, create_time
+         FROM im_group_ban
+         WHERE group_id = $1 AND (expire_time IS NULL OR expire_time > $2)
+         ORDER BY create_time DESC
+         "#,
+        group_id,
+        OffsetDateTime::now_utc()
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(bans)
+}
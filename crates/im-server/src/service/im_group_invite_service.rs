@@ -0,0 +1,186 @@
+use crate::db;
+use crate::models::{GroupRole, ImGroupInvite};
+use crate::prelude::*;
+use crate::service::im_group_ban_service;
+use crate::service::im_group_service::{self, GroupJoinOutcome};
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use time::{Duration, OffsetDateTime};
+
+/// 邀请令牌长度
+const INVITE_TOKEN_LEN: usize = 24;
+
+fn generate_invite_token() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(INVITE_TOKEN_LEN)
+        .map(char::from)
+        .collect()
+}
+
+/// 创建群邀请（只有群主和管理员可以创建）
+pub async fn create_invite(
+    group_id: &str,
+    operator_id: &str,
+    expire_seconds: Option<i64>,
+    max_uses: Option<i32>,
+    require_approval: bool,
+) -> AppResult<ImGroupInvite> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+
+    if expire_seconds.is_some_and(|seconds| seconds <= 0) {
+        return Err(AppError::public("有效期必须大于0"));
+    }
+    if max_uses.is_some_and(|max_uses| max_uses <= 0) {
+        return Err(AppError::public("最大使用次数必须大于0"));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let conn = db::pool();
+    let invite = sqlx::query_as!(
+        ImGroupInvite,
+        r#"
+        INSERT INTO im_group_invite
+         (invite_token, group_id, creator_id, expire_time, max_uses, use_count,
+          require_approval, revoked, create_time, update_time)
+         VALUES ($1, $2, $3, $4, $5, 0, $6, 0, $7, $7)
+         RETURNING *
+         "#,
+        generate_invite_token(),
+        group_id,
+        operator_id,
+        expire_seconds.map(|seconds| now + Duration::seconds(seconds)),
+        max_uses,
+        require_approval as i16,
+        now
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok(invite)
+}
+
+/// 获取群组的邀请列表（只有群主和管理员可以查看）
+pub async fn get_invites(group_id: &str, operator_id: &str) -> AppResult<Vec<ImGroupInvite>> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+
+    let conn = db::pool();
+    let invites = sqlx::query_as!(
+        ImGroupInvite,
+        r#"
+        SELECT invite_token, group_id, creator_id, expire_time, max_uses, use_count,
+                require_approval, revoked, create_time, update_time
+         FROM im_group_invite
+         WHERE group_id = $1
+         ORDER BY create_time DESC
+         "#,
+        group_id
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(invites)
+}
+
+/// 撤销群邀请（只有群主和管理员可以撤销）
+pub async fn revoke_invite(group_id: &str, invite_token: &str, operator_id: &str) -> AppResult<()> {
+    let group = im_group_service::get_group(group_id).await?;
+    im_group_service::ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+
+    let conn = db::pool();
+    let result = sqlx::query!(
+        r#"
+        UPDATE im_group_invite SET revoked = 1, update_time = $1
+         WHERE invite_token = $2 AND group_id = $3 AND revoked = 0
+         "#,
+        OffsetDateTime::now_utc(),
+        invite_token,
+        group_id
+    )
+    .execute(conn)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("邀请不存在或已撤销"));
+    }
+    Ok(())
+}
+
+/// 通过邀请令牌加入群组
+///
+/// 邀请需要审批时创建待审批的加群申请，否则直接入群
+pub async fn join_by_invite(
+    invite_token: &str,
+    user_id: &str,
+) -> AppResult<(ImGroupInvite, GroupJoinOutcome)> {
+    let conn = db::pool();
+    let now = OffsetDateTime::now_utc();
+    let invite = sqlx::query_as!(
+        ImGroupInvite,
+        r#"
+        SELECT invite_token, group_id, creator_id, expire_time, max_uses, use_count,
+                require_approval, revoked, create_time, update_time
+         FROM im_group_invite
+         WHERE invite_token = $1
+         "#,
+        invite_token
+    )
+    .fetch_optional(conn)
+    .await?
+    .filter(|invite| invite.is_usable(now))
+    .ok_or_else(|| AppError::public("邀请链接无效或已过期"))?;
+
+    let group = im_group_service::get_group(&invite.group_id).await?;
+    if im_group_service::get_group_member(&group.group_id, user_id)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::public("已经是群成员"));
+    }
+    im_group_ban_service::ensure_not_banned(&group.group_id, user_id).await?;
+    im_group_service::ensure_group_capacity(&group)?;
+
+    // 原子地占用一次使用次数，防止并发超出上限
+    let invite = sqlx::query_as!(
+        ImGroupInvite,
+        r#"
+        UPDATE im_group_invite SET use_count = use_count + 1, update_time = $1
+         WHERE invite_token = $2 AND revoked = 0
+         AND (expire_time IS NULL OR expire_time > $1)
+         AND (max_uses IS NULL OR use_count < max_uses)
+         RETURNING *
+         "#,
+        now,
+        invite.invite_token
+    )
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::public("邀请链接无效或已过期"))?;
+
+    let outcome = if invite.require_approval == 1 {
+        im_group_service::create_join_request(
+            &group.group_id,
+            user_id,
+            Some(f!("通过邀请链接申请加入（邀请人: {}）", invite.creator_id)),
+        )
+        .await
+        .map(GroupJoinOutcome::Pending)
+    } else {
+        im_group_service::add_group_member(&group.group_id, user_id, 0, None)
+            .await
+            .map(|_| GroupJoinOutcome::Joined)
+    };
+
+    match outcome {
+        Ok(outcome) => Ok((invite, outcome)),
+        Err(e) => {
+            // 入群失败时归还使用次数
+            sqlx::query!(
+                r#"UPDATE im_group_invite SET use_count = use_count - 1 WHERE invite_token = $1"#,
+                invite.invite_token
+            )
+            .execute(conn)
+            .await?;
+            Err(e)
+        }
+    }
+}
@@ -1,8 +1,21 @@
-use crate::dto::UpdateGroupRequest;
+use crate::dto::{
+    GroupMemberItem, GroupMemberListResp, GroupMemberQuery, GroupSearchItem, GroupSearchResp,
+    UpdateGroupRequest,
+};
+use crate::models::im_group::{GROUP_TYPE_PUBLIC, normalize_group_tags};
+use crate::models::im_group_join_request::{
+    JOIN_STATUS_APPROVED, JOIN_STATUS_PENDING, JOIN_STATUS_REJECTED,
+};
 use crate::prelude::*;
-use crate::{db, models::ImGroup, models::ImGroupMember};
+use crate::service::{im_group_avatar_service, im_group_ban_service};
+use crate::{
+    db, models::GroupRole, models::ImGroup, models::ImGroupJoinRequest, models::ImGroupMember,
+    models::ResolvedGroupMember, utils,
+};
+use im_share::redis::RedisClient;
 use time::OffsetDateTime;
 use tracing::{error, warn};
+use ulid::Ulid;
 
 pub async fn create_group(group: ImGroup) -> AppResult<()> {
     let conn = db::pool();
@@ -104,7 +117,39 @@ pub async fn add_group_member(
         );
         return Err(AppError::internal("群成员ID长度超过限制"));
     }
+    im_group_ban_service::ensure_not_banned(group_id, member_id).await?;
+    if get_group_member_by_user(group_id, member_id)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::public("该用户已是群成员"));
+    }
+
     let now = OffsetDateTime::now_utc();
+    let mut tx = conn.begin().await?;
+    // 锁定群组记录，串行化同一群组的入群操作，保证人数上限检查有效
+    // 临时群组（2人聊天）没有 im_group 记录，不限制人数
+    let max_member_count = sqlx::query_scalar!(
+        r#"SELECT max_member_count FROM im_group WHERE group_id = $1 FOR UPDATE"#,
+        group_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .flatten();
+    if max_member_count.is_some() {
+        let member_count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64" FROM im_group_member
+             WHERE group_id = $1 AND del_flag = 1
+             "#,
+            group_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        check_group_capacity(max_member_count, member_count)?;
+    }
+
+    // 只恢复已退出的成员记录，不覆盖在群成员的角色和群昵称
     let result = sqlx::query!(
         r#"
         INSERT INTO im_group_member
@@ -117,6 +162,7 @@ pub async fn add_group_member(
          del_flag = 1,
          update_time = $9,
          version = im_group_member.version + 1
+         WHERE im_group_member.del_flag = 0
         "#,
         group_member_id,
         group_id,
@@ -128,11 +174,17 @@ pub async fn add_group_member(
         now,
         now,
     )
-    .execute(conn)
+    .execute(&mut *tx)
     .await;
 
     match result {
-        Ok(_) => Ok(()),
+        Ok(result) if result.rows_affected() == 0 => Err(AppError::public("该用户已是群成员")),
+        Ok(_) => {
+            tx.commit().await?;
+            invalidate_group_member_cache(group_id).await;
+            im_group_avatar_service::schedule_regenerate(group_id);
+            Ok(())
+        }
         Err(e) => {
             error!("添加群成员数据库错误: {:?}", e);
             Err(AppError::internal(format!("添加群成员数据库错误: {:?}", e)))
@@ -148,7 +200,7 @@ pub async fn get_group_members(group_id: &str) -> AppResult<Vec<ImGroupMember>>
     let all_members = sqlx::query_as!(
         ImGroupMember,
         r#"
-        SELECT group_member_id, group_id, member_id, role, speak_date, mute, alias,
+        SELECT group_member_id, group_id, member_id, role, speak_date, mute, mute_end_time, alias,
                 join_time, leave_time, join_type, extra, del_flag, create_time, update_time, version
          FROM im_group_member
          WHERE group_id = $1 AND del_flag = 1
@@ -184,6 +236,259 @@ pub async fn get_group_members(group_id: &str) -> AppResult<Vec<ImGroupMember>>
     Ok(members)
 }
 
+/// 分页查询群成员（群成员可查看），支持按角色、禁言状态筛选和按群昵称/用户名搜索
+///
+/// 角色与 member_role 一致：群主以 im_group.owner_id 为准
+pub async fn query_group_members(
+    group_id: &str,
+    user_id: &str,
+    query: GroupMemberQuery,
+) -> AppResult<GroupMemberListResp> {
+    let group = get_group(group_id).await?;
+    ensure_group_role(&group, user_id, GroupRole::Member).await?;
+
+    let role = query
+        .role
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            GroupRole::from_name(name)
+                .map(|role| role as i32)
+                .ok_or_else(|| AppError::public(f!("无效的角色: {}", name)))
+        })
+        .transpose()?;
+    let pattern = query
+        .q
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty())
+        .map(|q| utils::like_contains_pattern(&q));
+    let current_page = query.current_page.max(1);
+    let page_size = query.page_size.clamp(1, 100);
+    let offset = (current_page - 1) * page_size;
+    let owner_id = group.owner_id.trim();
+    let now = OffsetDateTime::now_utc();
+
+    let conn = db::pool();
+    // member_id 可能是 open_id 或用户名，优先匹配 open_id；同一成员有重复记录时取最新的一条
+    let total = sqlx::query_scalar!(
+        r#"
+        WITH members AS (
+            SELECT DISTINCT ON (m.member_id) m.member_id, m.alias, m.mute, m.mute_end_time, u.name,
+                    CASE WHEN TRIM(m.member_id) = $2 THEN 2 WHEN m.role = 1 THEN 1 ELSE 0 END as role
+             FROM im_group_member m
+             LEFT JOIN users u ON u.open_id = m.member_id OR u.name = m.member_id
+             WHERE m.group_id = $1 AND m.del_flag = 1
+             ORDER BY m.member_id, (u.open_id = m.member_id) DESC NULLS LAST, m.update_time DESC
+        )
+        SELECT COUNT(*) as "count!: i64" FROM members
+         WHERE ($3::int IS NULL OR role = $3)
+         AND ($4::bool IS NULL
+              OR (mute = 0 AND (mute_end_time IS NULL OR mute_end_time > $5)) = $4)
+         AND ($6::varchar IS NULL OR alias ILIKE $6 ESCAPE '\' OR name ILIKE $6 ESCAPE '\'
+              OR member_id ILIKE $6 ESCAPE '\')
+         "#,
+        group_id,
+        owner_id,
+        role,
+        query.muted,
+        now,
+        pattern
+    )
+    .fetch_one(conn)
+    .await?;
+
+    let members = sqlx::query_as!(
+        GroupMemberItem,
+        r#"
+        WITH members AS (
+            SELECT DISTINCT ON (m.member_id) m.member_id, m.alias, m.mute, m.mute_end_time,
+                    m.join_time, m.speak_date, u.open_id, u.name,
+                    CASE WHEN TRIM(m.member_id) = $2 THEN 2 WHEN m.role = 1 THEN 1 ELSE 0 END as role
+             FROM im_group_member m
+             LEFT JOIN users u ON u.open_id = m.member_id OR u.name = m.member_id
+             WHERE m.group_id = $1 AND m.del_flag = 1
+             ORDER BY m.member_id, (u.open_id = m.member_id) DESC NULLS LAST, m.update_time DESC
+        )
+        SELECT member_id as "member_id!", open_id as "open_id?", name as "name?", alias,
+                role as "role!",
+                (mute = 0 AND (mute_end_time IS NULL OR mute_end_time > $5)) as "muted!",
+                mute_end_time, join_time, speak_date
+         FROM members
+         WHERE ($3::int IS NULL OR role = $3)
+         AND ($4::bool IS NULL
+              OR (mute = 0 AND (mute_end_time IS NULL OR mute_end_time > $5)) = $4)
+         AND ($6::varchar IS NULL OR alias ILIKE $6 ESCAPE '\' OR name ILIKE $6 ESCAPE '\'
+              OR member_id ILIKE $6 ESCAPE '\')
+         ORDER BY role DESC, join_time ASC NULLS LAST, member_id ASC
+         LIMIT $7 OFFSET $8
+         "#,
+        group_id,
+        owner_id,
+        role,
+        query.muted,
+        now,
+        pattern,
+        page_size,
+        offset
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(GroupMemberListResp {
+        members,
+        total,
+        current_page,
+        page_size,
+    })
+}
+
+/// 更新成员最后发言时间，member_id 可能是 open_id 或用户名
+pub async fn update_member_speak_date(
+    group_id: &str,
+    open_id: &str,
+    speak_date: OffsetDateTime,
+) -> AppResult<()> {
+    let conn = db::pool();
+    sqlx::query!(
+        r#"
+        UPDATE im_group_member SET speak_date = $1
+         WHERE group_id = $2 AND del_flag = 1
+         AND (member_id = $3 OR member_id IN (SELECT name FROM users WHERE open_id = $3))
+         "#,
+        speak_date,
+        group_id,
+        open_id
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 群成员缓存过期时间（秒）
+const GROUP_MEMBER_CACHE_TTL: u64 = 300;
+
+/// 群成员缓存：成员ID集合及其对应的用户解析结果
+struct GroupMemberCache {
+    member_ids: Vec<String>,
+    resolved: Vec<ResolvedGroupMember>,
+}
+
+/// 从数据库加载群成员，并在同一条查询中把成员ID解析为用户
+async fn load_group_member_cache(group_id: &str) -> AppResult<GroupMemberCache> {
+    let conn = db::pool();
+    // member_id 可能是 open_id 或用户名，优先匹配 open_id
+    let rows = sqlx::query!(
+        r#"
+        SELECT DISTINCT ON (m.member_id) m.member_id, u.id as "user_id?", u.open_id as "open_id?"
+         FROM im_group_member m
+         LEFT JOIN users u ON u.open_id = m.member_id OR u.name = m.member_id
+         WHERE m.group_id = $1 AND m.del_flag = 1
+         ORDER BY m.member_id, (u.open_id = m.member_id) DESC NULLS LAST
+         "#,
+        group_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    let mut member_ids = Vec::with_capacity(rows.len());
+    let mut resolved = Vec::with_capacity(rows.len());
+    for row in rows {
+        if let (Some(user_id), Some(open_id)) = (row.user_id, row.open_id) {
+            resolved.push(ResolvedGroupMember {
+                member_id: row.member_id.clone(),
+                open_id,
+                user_id,
+            });
+        } else {
+            warn!(group_id = %group_id, member_id = %row.member_id, "无法解析群成员用户");
+        }
+        member_ids.push(row.member_id);
+    }
+    Ok(GroupMemberCache {
+        member_ids,
+        resolved,
+    })
+}
+
+/// 获取群成员缓存（Redis 一次往返），缓存未命中时从数据库加载并回填
+async fn get_group_member_cache(group_id: &str) -> AppResult<GroupMemberCache> {
+    match RedisClient::get_group_members_cache(group_id).await {
+        Ok(Some((member_ids, users))) => {
+            let resolved: Result<Vec<ResolvedGroupMember>, _> = users
+                .values()
+                .map(|user_json| serde_json::from_str(user_json))
+                .collect();
+            match resolved {
+                Ok(resolved) => {
+                    return Ok(GroupMemberCache {
+                        member_ids,
+                        resolved,
+                    });
+                }
+                Err(e) => {
+                    warn!(group_id = %group_id, error = ?e, "反序列化群成员缓存失败");
+                }
+            }
+        }
+        Ok(None) => {}
+        Err(e) => {
+            warn!(group_id = %group_id, error = ?e, "读取群成员缓存失败");
+        }
+    }
+
+    // 先取版本号再查库，期间若成员变更则版本号已递增，回填会被放弃
+    let version = match RedisClient::get_group_members_version(group_id).await {
+        Ok(version) => Some(version),
+        Err(e) => {
+            warn!(group_id = %group_id, error = ?e, "读取群成员缓存版本失败");
+            None
+        }
+    };
+    let cache = load_group_member_cache(group_id).await?;
+    let Some(version) = version else {
+        return Ok(cache);
+    };
+    let users: Vec<(String, String)> = cache
+        .resolved
+        .iter()
+        .filter_map(|member| {
+            serde_json::to_string(member)
+                .ok()
+                .map(|user_json| (member.member_id.clone(), user_json))
+        })
+        .collect();
+    if let Err(e) = RedisClient::set_group_members_cache(
+        group_id,
+        version,
+        &cache.member_ids,
+        &users,
+        GROUP_MEMBER_CACHE_TTL,
+    )
+    .await
+    {
+        warn!(group_id = %group_id, error = ?e, "写入群成员缓存失败");
+    }
+    Ok(cache)
+}
+
+/// 获取群成员ID列表（优先读取缓存）
+pub async fn get_group_member_ids(group_id: &str) -> AppResult<Vec<String>> {
+    Ok(get_group_member_cache(group_id).await?.member_ids)
+}
+
+/// 获取已解析为用户的群成员列表（优先读取缓存），用于消息扇出
+pub async fn get_resolved_group_members(group_id: &str) -> AppResult<Vec<ResolvedGroupMember>> {
+    Ok(get_group_member_cache(group_id).await?.resolved)
+}
+
+/// 群成员变更提交后清除成员缓存，并递增版本号阻止并发读取回填旧数据
+pub async fn invalidate_group_member_cache(group_id: &str) {
+    if let Err(e) = RedisClient::del_group_members_cache(group_id).await {
+        warn!(group_id = %group_id, error = ?e, "清除群成员缓存失败");
+    }
+}
+
 /// 移除群成员（只有群主和管理员可以移除成员）
 pub async fn remove_group_member(
     group_id: &str,
@@ -201,74 +506,8 @@ pub async fn remove_group_member(
         }
     };
 
-    // 验证操作者权限：只有群主或管理员可以移除成员
-    let members = match get_group_members(group_id).await {
-        Ok(m) => m,
-        Err(e) => {
-            warn!("获取群成员列表失败: group_id={}, error={:?}", group_id, e);
-            return Err(e);
-        }
-    };
-
-    // 查找操作者的成员信息
-    let operator_member = members.iter().find(|m| m.member_id == operator_id);
-    let is_owner = group.owner_id.trim() == operator_id.trim();
-    let is_admin = operator_member.map(|m| m.role == 1).unwrap_or(false);
-
-    if !is_owner && !is_admin {
-        warn!(
-            "用户 {} 不是群主或管理员，无法移除成员: group_id={}",
-            operator_id, group_id
-        );
-        return Err(AppError::internal(f!(
-            "用户 {} 不是群主或管理员，无法移除成员: group_id={}",
-            operator_id,
-            group_id
-        )));
-    }
-
-    // 不能移除群主
-    if member_id.trim() == group.owner_id.trim() {
-        warn!(
-            "不能移除群主: group_id={}, member_id={}",
-            group_id, member_id
-        );
-        return Err(AppError::internal(f!(
-            "不能移除群主: group_id={}, member_id={}",
-            group_id,
-            member_id
-        )));
-    }
-
-    // 查找要移除的成员
-    let target_member = members.iter().find(|m| m.member_id == member_id);
-    if target_member.is_none() {
-        warn!(
-            "要移除的成员不存在: group_id={}, member_id={}",
-            group_id, member_id
-        );
-        return Err(AppError::not_found(f!(
-            "要移除的成员不存在: group_id={}, member_id={}",
-            group_id,
-            member_id
-        )));
-    }
-
-    // 管理员不能移除其他管理员（只有群主可以）
-    if !is_owner
-        && let Some(target) = target_member
-            && target.role == 1 {
-                warn!(
-                    "管理员不能移除其他管理员: group_id={}, operator_id={}, member_id={}",
-                    group_id, operator_id, member_id
-                );
-                return Err(AppError::internal(f!(
-                    "管理员不能移除其他管理员: group_id={}, operator_id={}, member_id={}",
-                    group_id,
-                    operator_id,
-                    member_id
-                )));
-            }
+    // 群主可以移除管理员和成员，管理员只能移除普通成员
+    ensure_can_manage_member(&group, operator_id, member_id).await?;
 
     let conn = db::pool();
 
@@ -285,6 +524,8 @@ pub async fn remove_group_member(
     )
     .execute(conn)
     .await?;
+    invalidate_group_member_cache(group_id).await;
+    im_group_avatar_service::schedule_regenerate(group_id);
 
     Ok(())
 }
@@ -307,29 +548,22 @@ pub async fn update_member_role(
         }
     };
 
-    if group.owner_id != operator_id {
-        warn!(
-            "用户 {} 不是群组 {} 的群主，无法修改成员角色",
-            operator_id, group_id
-        );
-        return Err(AppError::public(f!(
-            "用户 {} 不是群组 {} 的群主，无法修改成员角色",
-            operator_id,
-            group_id
-        )));
-    }
+    ensure_group_role(&group, operator_id, GroupRole::Owner).await?;
 
     // 验证角色值（0=普通成员，1=管理员，2=群主）
-    if !(0..=2).contains(&role) {
+    let Some(target_role) = GroupRole::from_role(role) else {
         warn!("无效的角色值: {}", role);
         return Err(AppError::public(f!("无效的角色值: {}", role)));
+    };
+    // 群主身份只能通过转让变更
+    if target_role == GroupRole::Owner {
+        warn!(
+            "不能直接设置群主: group_id={}, member_id={}",
+            group_id, member_id
+        );
+        return Err(AppError::public("请通过转让群主设置新群主"));
     }
-
-    // 不能修改群主的角色
-    if member_id == group.owner_id && role != 2 {
-        warn!("不能修改群主的角色");
-        return Err(AppError::public("不能修改群主的角色"));
-    }
+    ensure_can_manage_member(&group, operator_id, member_id).await?;
 
     let conn = db::pool();
     // 更新成员角色
@@ -391,6 +625,7 @@ pub async fn delete_group(group_id: &str, owner_id: &str) -> AppResult<()> {
     )
     .execute(conn)
     .await?;
+    invalidate_group_member_cache(group_id).await;
 
     Ok(())
 }
@@ -477,6 +712,7 @@ pub async fn dissolve_group(group_id: &str, owner_id: &str) -> AppResult<Vec<ImG
     )
     .execute(conn)
     .await?;
+    invalidate_group_member_cache(group_id).await;
 
     // 返回解散前的成员列表
     Ok(members)
@@ -542,17 +778,7 @@ pub async fn update_group(
         }
     };
 
-    // 去除空格进行比较（更宽松的匹配）
-    let group_owner_id_trimmed = group.owner_id.trim();
-    let owner_id_trimmed = owner_id.trim();
-
-    if group_owner_id_trimmed != owner_id_trimmed {
-        warn!(
-            "用户不是群主，无法更新群组信息: group_id={}, group_owner_id='{}', current_owner_id='{}'",
-            group_id, group_owner_id_trimmed, owner_id_trimmed
-        );
-        return Err(AppError::public("用户不是群主，无法更新群组信息"));
-    }
+    ensure_group_role(&group, owner_id, GroupRole::Owner).await?;
 
     // 使用 QueryBuilder 构建动态SQL，完全手动控制逗号
     let mut query_builder = sqlx::QueryBuilder::new("UPDATE im_group SET ");
@@ -627,6 +853,13 @@ pub async fn update_group(
             warn!("最大成员数必须大于0");
             return Err(AppError::public("最大成员数必须大于0".to_string()));
         }
+        if (max_member_count as i64) < group.member_count.unwrap_or(0) {
+            warn!(
+                "最大成员数小于当前成员数: group_id={}, max_member_count={}",
+                group_id, max_member_count
+            );
+            return Err(AppError::public("最大成员数不能小于当前成员数"));
+        }
         if need_comma {
             query_builder.push(", ");
         }
@@ -655,6 +888,11 @@ pub async fn update_group(
 
     query_builder.build().execute(conn).await?;
 
+    // 清空自定义头像后改用成员组合头像
+    if req.avatar.as_deref().is_some_and(|a| a.trim().is_empty()) {
+        im_group_avatar_service::schedule_regenerate(group_id);
+    }
+
     Ok(())
 }
 
@@ -718,3 +956,633 @@ pub async fn get_user_groups(user_id: &str) -> AppResult<Vec<ImGroup>> {
 
     Ok(groups)
 }
+
+/// 获取单个群成员（仅有效成员）
+pub async fn get_group_member(group_id: &str, member_id: &str) -> AppResult<Option<ImGroupMember>> {
+    let conn = db::pool();
+    let member = sqlx::query_as!(
+        ImGroupMember,
+        r#"
+        SELECT group_member_id, group_id, member_id, role, speak_date, mute, mute_end_time, alias,
+                join_time, leave_time, join_type, extra, del_flag, create_time, update_time, version
+         FROM im_group_member
+         WHERE group_id = $1 AND member_id = $2 AND del_flag = 1
+         ORDER BY update_time DESC
+         LIMIT 1
+         "#,
+        group_id,
+        member_id
+    )
+    .fetch_optional(conn)
+    .await?;
+    Ok(member)
+}
+
+/// 按用户获取群成员，member_id 可能是 open_id 或用户名
+pub async fn get_group_member_by_user(
+    group_id: &str,
+    open_id: &str,
+) -> AppResult<Option<ImGroupMember>> {
+    let conn = db::pool();
+    let member = sqlx::query_as!(
+        ImGroupMember,
+        r#"
+        SELECT group_member_id, group_id, member_id, role, speak_date, mute, mute_end_time, alias,
+                join_time, leave_time, join_type, extra, del_flag, create_time, update_time, version
+         FROM im_group_member
+         WHERE group_id = $1 AND del_flag = 1
+         AND (member_id = $2 OR member_id IN (SELECT name FROM users WHERE open_id = $2))
+         ORDER BY (member_id = $2) DESC, update_time DESC
+         LIMIT 1
+         "#,
+        group_id,
+        open_id
+    )
+    .fetch_optional(conn)
+    .await?;
+    Ok(member)
+}
+
+/// 成员在群内的角色，群主以 im_group.owner_id 为准
+fn member_role(group: &ImGroup, member: &ImGroupMember) -> GroupRole {
+    if group.owner_id.trim() == member.member_id.trim() {
+        GroupRole::Owner
+    } else {
+        GroupRole::from_role(member.role)
+            .filter(|role| *role != GroupRole::Owner)
+            .unwrap_or(GroupRole::Member)
+    }
+}
+
+/// 获取用户在群内的角色，成员记录按 open_id 或用户名解析，非群成员返回 None
+pub async fn get_member_role(group: &ImGroup, member_id: &str) -> AppResult<Option<GroupRole>> {
+    if group.owner_id.trim() == member_id.trim() {
+        return Ok(Some(GroupRole::Owner));
+    }
+    Ok(get_group_member_by_user(&group.group_id, member_id)
+        .await?
+        .map(|member| member_role(group, &member)))
+}
+
+/// 校验操作者在群内的角色不低于 required，返回操作者的角色
+pub async fn ensure_group_role(
+    group: &ImGroup,
+    operator_id: &str,
+    required: GroupRole,
+) -> AppResult<GroupRole> {
+    match get_member_role(group, operator_id).await? {
+        Some(role) if role >= required => Ok(role),
+        Some(_) => Err(AppError::public(match required {
+            GroupRole::Owner => "只有群主可以执行此操作",
+            _ => "只有群主或管理员可以执行此操作",
+        })),
+        None => Err(AppError::public("不是群成员")),
+    }
+}
+
+/// 校验操作者可以管理目标成员：操作者至少为管理员，且角色必须高于目标成员
+pub async fn ensure_can_manage_member(
+    group: &ImGroup,
+    operator_id: &str,
+    member_id: &str,
+) -> AppResult<ImGroupMember> {
+    let operator_role = ensure_group_role(group, operator_id, GroupRole::Admin).await?;
+    let target = get_group_member(&group.group_id, member_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("群成员不存在"))?;
+    let target_role = member_role(group, &target);
+    if target_role >= operator_role {
+        warn!(
+            "权限不足: group_id={}, operator_id={}, member_id={}, target_role={:?}",
+            group.group_id, operator_id, member_id, target_role
+        );
+        return Err(AppError::public(match target_role {
+            GroupRole::Owner => "不能操作群主",
+            _ => "管理员不能操作其他管理员",
+        }));
+    }
+    Ok(target)
+}
+
+/// 校验群成员数是否已达上限
+pub fn ensure_group_capacity(group: &ImGroup) -> AppResult<()> {
+    match group.member_count {
+        Some(member_count) => check_group_capacity(group.max_member_count, member_count),
+        None => Ok(()),
+    }
+}
+
+/// 按当前成员数校验人数上限，max_member_count 为空表示不限制
+fn check_group_capacity(max_member_count: Option<i32>, member_count: i64) -> AppResult<()> {
+    if let Some(max_member_count) = max_member_count
+        && member_count >= max_member_count as i64
+    {
+        return Err(AppError::public(f!(
+            "群成员已满（上限{}人）",
+            max_member_count
+        )));
+    }
+    Ok(())
+}
+
+/// 加群申请的处理结果
+#[derive(Debug, Clone)]
+pub enum GroupJoinOutcome {
+    /// 群组允许自由加入，已直接入群
+    Joined,
+    /// 需要群主或管理员审批
+    Pending(ImGroupJoinRequest),
+}
+
+/// 申请加入群组
+///
+/// apply_join_type 为 2（自由加入）且未开启群验证时直接入群，为 0 时禁止申请，其余情况创建待审批申请
+pub async fn apply_join_group(
+    group_id: &str,
+    user_id: &str,
+    message: Option<String>,
+) -> AppResult<GroupJoinOutcome> {
+    let group = get_group(group_id).await?;
+    if group.apply_join_type == 0 {
+        return Err(AppError::public("该群禁止申请加入"));
+    }
+    if get_group_member(group_id, user_id).await?.is_some() {
+        return Err(AppError::public("已经是群成员"));
+    }
+    if let Some(ref message) = message
+        && message.chars().count() > 255
+    {
+        return Err(AppError::public("申请附言不能超过255个字符"));
+    }
+    ensure_group_capacity(&group)?;
+
+    if group.apply_join_type == 2 && group.verifier != Some(1) {
+        add_group_member(group_id, user_id, 0, None).await?;
+        info!(
+            "用户自由加入群组: group_id={}, user_id={}",
+            group_id, user_id
+        );
+        return Ok(GroupJoinOutcome::Joined);
+    }
+
+    create_join_request(group_id, user_id, message)
+        .await
+        .map(GroupJoinOutcome::Pending)
+}
+
+/// 创建待审批的加群申请，同一用户同一群组只能有一个待处理申请
+pub async fn create_join_request(
+    group_id: &str,
+    user_id: &str,
+    message: Option<String>,
+) -> AppResult<ImGroupJoinRequest> {
+    im_group_ban_service::ensure_not_banned(group_id, user_id).await?;
+    let conn = db::pool();
+    let pending_count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!: i64" FROM im_group_join_request
+         WHERE group_id = $1 AND from_id = $2 AND approve_status = $3 AND del_flag = 1
+         "#,
+        group_id,
+        user_id,
+        JOIN_STATUS_PENDING
+    )
+    .fetch_one(conn)
+    .await?;
+    if pending_count > 0 {
+        return Err(AppError::public("已提交过申请，请等待审核"));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let request = sqlx::query_as!(
+        ImGroupJoinRequest,
+        r#"
+        INSERT INTO im_group_join_request
+         (request_id, group_id, from_id, message, approve_status, create_time, update_time,
+          del_flag, version)
+         VALUES ($1, $2, $3, $4, $5, $6, $6, 1, 1)
+         RETURNING *
+         "#,
+        Ulid::new().to_string(),
+        group_id,
+        user_id,
+        message,
+        JOIN_STATUS_PENDING,
+        now
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(request)
+}
+
+/// 获取加群申请列表（只有群主和管理员可以查看）
+pub async fn get_group_join_requests(
+    group_id: &str,
+    operator_id: &str,
+    approve_status: Option<i32>,
+) -> AppResult<Vec<ImGroupJoinRequest>> {
+    let group = get_group(group_id).await?;
+    ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+
+    let conn = db::pool();
+    let requests = sqlx::query_as!(
+        ImGroupJoinRequest,
+        r#"
+        SELECT request_id, group_id, from_id, message, approve_status, handler_id,
+                create_time, update_time, del_flag, version
+         FROM im_group_join_request
+         WHERE group_id = $1 AND del_flag = 1
+         AND ($2::integer IS NULL OR approve_status = $2)
+         ORDER BY create_time DESC
+         "#,
+        group_id,
+        approve_status
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(requests)
+}
+
+/// 处理加群申请（只有群主和管理员可以处理）
+pub async fn handle_group_join_request(
+    group_id: &str,
+    request_id: &str,
+    operator_id: &str,
+    approve_status: i32,
+) -> AppResult<ImGroupJoinRequest> {
+    if approve_status != JOIN_STATUS_APPROVED && approve_status != JOIN_STATUS_REJECTED {
+        return Err(AppError::public("无效的审批状态"));
+    }
+
+    let group = get_group(group_id).await?;
+    ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+
+    let conn = db::pool();
+    if approve_status == JOIN_STATUS_APPROVED {
+        ensure_group_capacity(&group)?;
+        // 申请提交后申请人可能被加入黑名单，审批通过前再次检查
+        let from_id = sqlx::query_scalar!(
+            r#"
+            SELECT from_id FROM im_group_join_request
+             WHERE request_id = $1 AND group_id = $2 AND approve_status = $3 AND del_flag = 1
+             "#,
+            request_id,
+            group_id,
+            JOIN_STATUS_PENDING
+        )
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("加群申请不存在或已处理"))?;
+        im_group_ban_service::ensure_not_banned(group_id, &from_id).await?;
+    }
+
+    let request = sqlx::query_as!(
+        ImGroupJoinRequest,
+        r#"
+        UPDATE im_group_join_request
+         SET approve_status = $1, handler_id = $2, update_time = $3, version = version + 1
+         WHERE request_id = $4 AND group_id = $5 AND approve_status = $6 AND del_flag = 1
+         RETURNING *
+         "#,
+        approve_status,
+        operator_id,
+        OffsetDateTime::now_utc(),
+        request_id,
+        group_id,
+        JOIN_STATUS_PENDING
+    )
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::not_found("加群申请不存在或已处理"))?;
+
+    if approve_status == JOIN_STATUS_APPROVED
+        && let Err(e) = add_group_member(group_id, &request.from_id, 0, None).await
+    {
+        // 入群失败时恢复为待处理，申请可以再次处理
+        warn!(
+            "同意加群申请后入群失败，恢复为待处理: group_id={}, request_id={}, error={:?}",
+            group_id, request_id, e
+        );
+        sqlx::query!(
+            r#"
+            UPDATE im_group_join_request
+             SET approve_status = $1, handler_id = NULL, update_time = $2, version = version + 1
+             WHERE request_id = $3 AND group_id = $4 AND approve_status = $5
+             "#,
+            JOIN_STATUS_PENDING,
+            OffsetDateTime::now_utc(),
+            request_id,
+            group_id,
+            JOIN_STATUS_APPROVED
+        )
+        .execute(conn)
+        .await?;
+        return Err(e);
+    }
+
+    Ok(request)
+}
+
+/// 获取群主和管理员的用户ID
+pub async fn get_group_manager_ids(group_id: &str) -> AppResult<Vec<String>> {
+    let conn = db::pool();
+    let ids = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT member_id FROM im_group_member
+         WHERE group_id = $1 AND del_flag = 1 AND role >= 1
+         "#,
+        group_id
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(ids)
+}
+
+/// 转让群主（只有群主可以转让）
+///
+/// 在同一事务中将原群主降为管理员、目标成员升为群主，并更新 im_group.owner_id
+pub async fn transfer_ownership(
+    group_id: &str,
+    owner_id: &str,
+    new_owner_id: &str,
+) -> AppResult<()> {
+    let group = get_group(group_id).await?;
+    ensure_group_role(&group, owner_id, GroupRole::Owner).await?;
+    if new_owner_id == owner_id {
+        return Err(AppError::public("不能转让给自己"));
+    }
+    if get_group_member(group_id, new_owner_id).await?.is_none() {
+        return Err(AppError::public("新群主必须是群成员"));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let conn = db::pool();
+    let mut tx = conn.begin().await?;
+
+    let result = sqlx::query!(
+        r#"UPDATE im_group
+         SET owner_id = $1, update_time = $2, version = version + 1
+         WHERE group_id = $3 AND owner_id = $4 AND del_flag = 1"#,
+        new_owner_id,
+        now,
+        group_id,
+        group.owner_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::public("群主已变更，请刷新后重试"));
+    }
+
+    sqlx::query!(
+        r#"UPDATE im_group_member
+         SET role = 1, update_time = $1, version = version + 1
+         WHERE group_id = $2 AND member_id = $3 AND del_flag = 1"#,
+        now,
+        group_id,
+        owner_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"UPDATE im_group_member
+         SET role = 2, update_time = $1, version = version + 1
+         WHERE group_id = $2 AND member_id = $3 AND del_flag = 1"#,
+        now,
+        group_id,
+        new_owner_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    info!(
+        "群主已转让: group_id={}, from={}, to={}",
+        group_id, owner_id, new_owner_id
+    );
+    Ok(())
+}
+
+/// 主动退出群组，群主需先转让群主
+pub async fn leave_group(group_id: &str, member_id: &str) -> AppResult<()> {
+    let group = get_group(group_id).await?;
+    if group.owner_id.trim() == member_id.trim() {
+        return Err(AppError::public("群主需先转让群主后才能退出群组"));
+    }
+
+    let conn = db::pool();
+    let now = OffsetDateTime::now_utc();
+    let result = sqlx::query!(
+        r#"
+        UPDATE im_group_member
+         SET del_flag = 0, leave_time = $1, update_time = $1, version = version + 1
+         WHERE group_id = $2 AND member_id = $3 AND del_flag = 1
+         "#,
+        now,
+        group_id,
+        member_id
+    )
+    .execute(conn)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("不是群成员"));
+    }
+    invalidate_group_member_cache(group_id).await;
+    im_group_avatar_service::schedule_regenerate(group_id);
+    Ok(())
+}
+
+/// 设置全员禁言（只有群主和管理员可以设置）
+pub async fn set_group_mute(group_id: &str, operator_id: &str, mute: bool) -> AppResult<()> {
+    let group = get_group(group_id).await?;
+    ensure_group_role(&group, operator_id, GroupRole::Admin).await?;
+
+    let conn = db::pool();
+    sqlx::query!(
+        r#"UPDATE im_group SET mute = $1, update_time = $2, version = version + 1
+         WHERE group_id = $3 AND del_flag = 1"#,
+        if mute { 0i16 } else { 1i16 },
+        OffsetDateTime::now_utc(),
+        group_id
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 设置成员禁言（群主可禁言管理员和成员，管理员只能禁言普通成员）
+///
+/// mute_end_time 为空表示永久禁言，到期后自动解除
+pub async fn set_member_mute(
+    group_id: &str,
+    operator_id: &str,
+    member_id: &str,
+    mute: bool,
+    mute_end_time: Option<OffsetDateTime>,
+) -> AppResult<()> {
+    if operator_id == member_id {
+        return Err(AppError::public("不能禁言自己"));
+    }
+    let group = get_group(group_id).await?;
+    ensure_can_manage_member(&group, operator_id, member_id).await?;
+
+    let now = OffsetDateTime::now_utc();
+    if mute && mute_end_time.is_some_and(|end_time| end_time <= now) {
+        return Err(AppError::public("禁言截止时间必须晚于当前时间"));
+    }
+
+    let conn = db::pool();
+    sqlx::query!(
+        r#"UPDATE im_group_member
+         SET mute = $1, mute_end_time = $2, update_time = $3, version = version + 1
+         WHERE group_id = $4 AND member_id = $5 AND del_flag = 1"#,
+        if mute { 0i16 } else { 1i16 },
+        if mute { mute_end_time } else { None },
+        now,
+        group_id,
+        member_id
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 校验用户是否可以在群内发言（群主和管理员不受全员禁言限制），非群成员不能发言
+pub async fn ensure_can_speak(group: &ImGroup, open_id: &str) -> AppResult<()> {
+    let Some(member) = get_group_member_by_user(&group.group_id, open_id).await? else {
+        if group.owner_id.trim() == open_id.trim() {
+            return Ok(());
+        }
+        return Err(AppError::public("不是群成员"));
+    };
+    if member_role(group, &member) == GroupRole::Owner {
+        return Ok(());
+    }
+    if member_role(group, &member) == GroupRole::Member && group.is_all_muted() {
+        return Err(AppError::public("群组已开启全员禁言"));
+    }
+    if member.is_muted(OffsetDateTime::now_utc()) {
+        return Err(AppError::public("你已被禁言"));
+    }
+    Ok(())
+}
+
+/// 设置群标签（只有群主可以设置，覆盖原有标签），返回规范化后的标签
+pub async fn set_group_tags(
+    group_id: &str,
+    owner_id: &str,
+    tags: &[String],
+) -> AppResult<Vec<String>> {
+    let tags = normalize_group_tags(tags).map_err(AppError::public)?;
+    let group = get_group(group_id).await?;
+    ensure_group_role(&group, owner_id, GroupRole::Owner).await?;
+
+    let conn = db::pool();
+    let mut tx = conn.begin().await?;
+    sqlx::query!(r#"DELETE FROM im_group_tag WHERE group_id = $1"#, group_id)
+        .execute(&mut *tx)
+        .await?;
+    if !tags.is_empty() {
+        sqlx::query!(
+            r#"
+            INSERT INTO im_group_tag (group_id, tag, create_time)
+             SELECT $1, tag, $3 FROM UNNEST($2::varchar[]) AS tag
+             "#,
+            group_id,
+            &tags,
+            OffsetDateTime::now_utc()
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(tags)
+}
+
+/// 获取群标签
+pub async fn get_group_tags(group_id: &str) -> AppResult<Vec<String>> {
+    let conn = db::pool();
+    let tags = sqlx::query_scalar!(
+        r#"SELECT tag FROM im_group_tag WHERE group_id = $1 ORDER BY tag ASC"#,
+        group_id
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(tags)
+}
+
+/// 搜索公开群（未解散），关键字匹配群名称、群简介和标签，按成员数倒序分页返回
+pub async fn search_public_groups(
+    q: Option<String>,
+    tag: Option<String>,
+    current_page: i64,
+    page_size: i64,
+) -> AppResult<GroupSearchResp> {
+    let conn = db::pool();
+    let current_page = current_page.max(1);
+    let page_size = page_size.clamp(1, 50);
+    let offset = (current_page - 1) * page_size;
+    let pattern = q
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty())
+        .map(|q| utils::like_contains_pattern(&q));
+    let tag = tag
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty());
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!: i64" FROM im_group g
+         WHERE g.group_type = $1 AND g.del_flag = 1
+         AND ($2::varchar IS NULL OR g.group_name ILIKE $2 ESCAPE '\'
+              OR g.introduction ILIKE $2 ESCAPE '\'
+              OR EXISTS (SELECT 1 FROM im_group_tag t
+                         WHERE t.group_id = g.group_id AND t.tag ILIKE $2 ESCAPE '\'))
+         AND ($3::varchar IS NULL
+              OR EXISTS (SELECT 1 FROM im_group_tag t WHERE t.group_id = g.group_id AND t.tag = $3))
+         "#,
+        GROUP_TYPE_PUBLIC,
+        pattern,
+        tag
+    )
+    .fetch_one(conn)
+    .await?;
+
+    let groups = sqlx::query_as!(
+        GroupSearchItem,
+        r#"
+        SELECT g.group_id, g.group_name, g.avatar, g.introduction, g.apply_join_type, g.max_member_count,
+                (SELECT COUNT(*) FROM im_group_member gm WHERE gm.group_id = g.group_id AND gm.del_flag = 1) as "member_count!",
+                COALESCE(
+                    (SELECT array_agg(t.tag ORDER BY t.tag) FROM im_group_tag t WHERE t.group_id = g.group_id),
+                    '{}'
+                ) as "tags!: Vec<String>"
+         FROM im_group g
+         WHERE g.group_type = $1 AND g.del_flag = 1
+         AND ($2::varchar IS NULL OR g.group_name ILIKE $2 ESCAPE '\'
+              OR g.introduction ILIKE $2 ESCAPE '\'
+              OR EXISTS (SELECT 1 FROM im_group_tag t
+                         WHERE t.group_id = g.group_id AND t.tag ILIKE $2 ESCAPE '\'))
+         AND ($3::varchar IS NULL
+              OR EXISTS (SELECT 1 FROM im_group_tag t WHERE t.group_id = g.group_id AND t.tag = $3))
+         ORDER BY "member_count!" DESC, g.create_time DESC
+         LIMIT $4 OFFSET $5
+         "#,
+        GROUP_TYPE_PUBLIC,
+        pattern,
+        tag,
+        page_size,
+        offset
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(GroupSearchResp {
+        groups,
+        total,
+        current_page,
+        page_size,
+    })
+}
@@ -0,0 +1,217 @@
+use crate::db;
+use crate::dto::MessagePinResp;
+use crate::models::im_message_pin::MAX_PINS_PER_CHAT;
+use crate::models::{GroupRole, ImMessagePin};
+use crate::prelude::*;
+use crate::service::im_group_service;
+use time::OffsetDateTime;
+
+/// 置顶消息所在的会话
+pub enum PinChat {
+    /// 群聊
+    Group { group_id: String },
+    /// 单聊：peer_id 为对方用户ID
+    Single { peer_id: String },
+}
+
+/// 解析会话并校验权限：群聊查看需要是群成员，置顶和取消置顶需要群主或管理员；单聊双方都可以操作
+async fn resolve_chat(chat_id: &str, user_id: &str, required: GroupRole) -> AppResult<PinChat> {
+    // 群聊会话ID为 group_ 前缀加群组ID，群组ID本身也可能带有该前缀
+    let group = match im_group_service::get_group(chat_id).await {
+        Ok(group) => Some(group),
+        Err(_) => match chat_id.strip_prefix("group_") {
+            Some(group_id) => im_group_service::get_group(group_id).await.ok(),
+            None => None,
+        },
+    };
+    if let Some(group) = group {
+        im_group_service::ensure_group_role(&group, user_id, required).await?;
+        return Ok(PinChat::Group {
+            group_id: group.group_id,
+        });
+    }
+
+    let conn = db::pool();
+    let peer_id = sqlx::query_scalar!(
+        r#"
+        SELECT to_id FROM im_chat
+         WHERE chat_id = $1 AND owner_id = $2 AND (del_flag IS NULL OR del_flag = 1)
+         LIMIT 1
+         "#,
+        chat_id,
+        user_id
+    )
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::not_found("会话不存在"))?;
+    Ok(PinChat::Single { peer_id })
+}
+
+/// 清理消息已被撤回或删除（del_flag = 0）的置顶记录
+async fn remove_stale_pins(chat_id: &str) -> AppResult<()> {
+    let conn = db::pool();
+    sqlx::query!(
+        r#"
+        DELETE FROM im_message_pin p
+         WHERE p.chat_id = $1
+         AND NOT EXISTS (
+             SELECT 1 FROM im_group_message g
+             WHERE g.message_id = p.message_id AND g.del_flag = 1
+         )
+         AND NOT EXISTS (
+             SELECT 1 FROM im_single_message s
+             WHERE s.message_id = p.message_id AND s.del_flag = 1
+         )
+         "#,
+        chat_id
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 群消息是否存在且未撤回，群消息的 group_id 与会话ID一致（带 group_ 前缀）
+async fn group_message_exists(group_id: &str, message_id: &str) -> AppResult<bool> {
+    let conn = db::pool();
+    let exists = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM im_group_message
+            WHERE message_id = $1 AND group_id = $2 AND del_flag = 1
+        ) as "exists!"
+        "#,
+        message_id,
+        group_id
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok(exists)
+}
+
+/// 单聊消息是否存在且未撤回，消息必须是用户与会话对方之间的消息
+async fn single_message_exists(user_id: &str, peer_id: &str, message_id: &str) -> AppResult<bool> {
+    let conn = db::pool();
+    let exists = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM im_single_message
+            WHERE message_id = $1 AND del_flag = 1
+            AND ((from_id = $2 AND to_id = $3) OR (from_id = $3 AND to_id = $2))
+        ) as "exists!"
+        "#,
+        message_id,
+        user_id,
+        peer_id
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok(exists)
+}
+
+/// 置顶消息（单聊双方都可以置顶，群聊只有群主和管理员可以置顶）
+pub async fn pin_message(
+    chat_id: &str,
+    operator_id: &str,
+    message_id: &str,
+) -> AppResult<(ImMessagePin, PinChat)> {
+    let chat = resolve_chat(chat_id, operator_id, GroupRole::Admin).await?;
+
+    let message_exists = match &chat {
+        PinChat::Group { .. } => group_message_exists(chat_id, message_id).await?,
+        PinChat::Single { peer_id } => {
+            single_message_exists(operator_id, peer_id, message_id).await?
+        }
+    };
+    if !message_exists {
+        return Err(AppError::not_found("消息不存在或已撤回"));
+    }
+
+    remove_stale_pins(chat_id).await?;
+
+    // 同一会话的置顶串行执行，避免并发置顶时数量检查失效
+    let conn = db::pool();
+    let mut tx = conn.begin().await?;
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", chat_id)
+        .execute(&mut *tx)
+        .await?;
+    let pin_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!: i64" FROM im_message_pin WHERE chat_id = $1"#,
+        chat_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if pin_count >= MAX_PINS_PER_CHAT {
+        return Err(AppError::public(f!(
+            "每个会话最多置顶{}条消息",
+            MAX_PINS_PER_CHAT
+        )));
+    }
+
+    let pin = sqlx::query_as!(
+        ImMessagePin,
+        r#"
+        INSERT INTO im_message_pin (chat_id, message_id, pinned_by, pin_time)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (chat_id, message_id) DO NOTHING
+         RETURNING chat_id, message_id, pinned_by, pin_time
+         "#,
+        chat_id,
+        message_id,
+        operator_id,
+        OffsetDateTime::now_utc()
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::public("消息已置顶"))?;
+    tx.commit().await?;
+    Ok((pin, chat))
+}
+
+/// 取消置顶（权限与置顶相同）
+pub async fn unpin_message(
+    chat_id: &str,
+    operator_id: &str,
+    message_id: &str,
+) -> AppResult<PinChat> {
+    let chat = resolve_chat(chat_id, operator_id, GroupRole::Admin).await?;
+
+    let conn = db::pool();
+    let result = sqlx::query!(
+        r#"DELETE FROM im_message_pin WHERE chat_id = $1 AND message_id = $2"#,
+        chat_id,
+        message_id
+    )
+    .execute(conn)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("消息未置顶"));
+    }
+    Ok(chat)
+}
+
+/// 获取会话的置顶消息，最新置顶的在前；消息已撤回或删除的置顶会被自动移除
+pub async fn get_pins(chat_id: &str, user_id: &str) -> AppResult<Vec<MessagePinResp>> {
+    resolve_chat(chat_id, user_id, GroupRole::Member).await?;
+    remove_stale_pins(chat_id).await?;
+
+    let conn = db::pool();
+    let pins = sqlx::query_as!(
+        MessagePinResp,
+        r#"
+        SELECT p.chat_id, p.message_id, p.pinned_by, p.pin_time,
+                COALESCE(g.from_id, s.from_id) as "from_id!",
+                COALESCE(g.message_body, s.message_body) as "message_body!",
+                COALESCE(g.message_content_type, s.message_content_type) as "message_content_type!",
+                COALESCE(g.message_time, s.message_time) as "message_time!"
+         FROM im_message_pin p
+         LEFT JOIN im_group_message g ON g.message_id = p.message_id AND g.del_flag = 1
+         LEFT JOIN im_single_message s ON s.message_id = p.message_id AND s.del_flag = 1
+         WHERE p.chat_id = $1 AND (g.message_id IS NOT NULL OR s.message_id IS NOT NULL)
+         ORDER BY p.pin_time DESC
+         "#,
+        chat_id
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(pins)
+}
@@ -102,10 +102,21 @@ pub async fn mark_single_message_read(message_id: &str, to_id: &str) -> AppResul
 }
 
 /// 保存群聊消息
-pub async fn save_group_message(message: ImGroupMessage) -> AppResult<()> {
+/// 保存群聊消息，返回分配的群内序列号
+///
+/// 序列号在群内严格递增且不小于消息时间戳，读扩散时客户端据此拉取
+pub async fn save_group_message(message: ImGroupMessage) -> AppResult<i64> {
     let conn = db::pool();
     let now = OffsetDateTime::now_utc();
-    sqlx::query!(
+    let mut tx = conn.begin().await?;
+    let sequence = next_group_message_sequence(
+        &mut tx,
+        &message.group_id,
+        message.sequence.unwrap_or(now.unix_timestamp() * 1000),
+    )
+    .await?;
+    // 重复保存时返回已有消息的序列号
+    let sequence = sqlx::query_scalar!(
         r#"
         INSERT INTO im_group_message
          (message_id, group_id, from_id, message_body, message_time, message_content_type,
@@ -113,6 +124,7 @@ pub async fn save_group_message(message: ImGroupMessage) -> AppResult<()> {
          VALUES ($1, $2, $3, $4, $5, $6, $7, 1, $8, $9, $10, $11, 1, $12)
          ON CONFLICT (message_id) DO UPDATE SET
          message_id = EXCLUDED.message_id
+         RETURNING sequence as "sequence!"
         "#,
         message.message_id,
         message.group_id,
@@ -121,19 +133,42 @@ pub async fn save_group_message(message: ImGroupMessage) -> AppResult<()> {
         message.message_time,
         message.message_content_type,
         message.extra,
-        message.sequence,
+        sequence,
         message.message_random,
         now,
         now,
         message.reply_to
     )
-    .execute(conn)
+    .fetch_one(&mut *tx)
     .await?;
+    tx.commit().await?;
 
-    Ok(())
+    Ok(sequence)
+}
+
+/// 分配群消息序列号：取当前最大序列号加一与时间戳中的较大值
+async fn next_group_message_sequence(
+    conn: &mut sqlx::PgConnection,
+    group_id: &str,
+    timestamp_ms: i64,
+) -> AppResult<i64> {
+    let sequence = sqlx::query_scalar!(
+        r#"
+            INSERT INTO im_group_message_sequence (group_id, sequence)
+            VALUES ($1, $2)
+            ON CONFLICT (group_id) DO UPDATE SET
+            sequence = GREATEST(im_group_message_sequence.sequence + 1, EXCLUDED.sequence)
+            RETURNING sequence
+        "#,
+        group_id,
+        timestamp_ms
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok(sequence)
 }
 
-/// 获取群聊消息列表
+/// 获取群聊消息列表：返回序列号大于 since_sequence 的消息，按序列号升序
 /// 重要：过滤掉通话邀请消息（message_content_type = 4），因为通话邀请是实时消息，过期后没有意义
 pub async fn get_group_messages(
     group_id: &str,
@@ -141,22 +176,24 @@ pub async fn get_group_messages(
     limit: i32,
 ) -> AppResult<Vec<ImGroupMessage>> {
     let conn = db::pool();
-    let mut query = "SELECT message_id, group_id, from_id, message_body, message_time, message_content_type,
-                            extra, del_flag, sequence, message_random, create_time, update_time, version, reply_to
-                     FROM im_group_message
-                     WHERE group_id = ? AND del_flag = 1 AND message_content_type != 4".to_string();
-
-    if let Some(seq) = since_sequence {
-        query.push_str(&format!(" AND sequence > {}", seq));
-    }
-
-    query.push_str(" ORDER BY sequence ASC LIMIT ?");
-
-    let messages = sqlx::query_as::<_, ImGroupMessage>(&query)
-        .bind(group_id)
-        .bind(limit)
-        .fetch_all(conn)
-        .await?;
+    let messages = sqlx::query_as!(
+        ImGroupMessage,
+        r#"
+        SELECT message_id, group_id, from_id, message_body, message_time, message_content_type,
+               extra, del_flag, sequence, message_random, create_time as "create_time!",
+               update_time, version, reply_to
+         FROM im_group_message
+         WHERE group_id = $1 AND del_flag = 1 AND message_content_type != 4
+         AND ($2::bigint IS NULL OR sequence > $2)
+         ORDER BY sequence ASC
+         LIMIT $3
+        "#,
+        group_id,
+        since_sequence,
+        limit as i64
+    )
+    .fetch_all(conn)
+    .await?;
 
     Ok(messages)
 }
@@ -1,6 +1,7 @@
 use time::OffsetDateTime;
 
 use crate::db;
+use crate::models::im_user::{FRIEND_ALLOW_NO_VERIFY, FRIEND_ALLOW_NOBODY};
 use crate::models::{ImSafeUser, ImUser, ImUserData};
 use crate::prelude::*;
 use im_share::redis::RedisClient;
@@ -400,6 +401,10 @@ pub async fn get_user_data(user_id: &str) -> AppResult<ImUserData> {
 }
 
 pub async fn upsert_user_data(user_data: ImUserData) -> AppResult<()> {
+    if !(FRIEND_ALLOW_NO_VERIFY..=FRIEND_ALLOW_NOBODY).contains(&user_data.friend_allow_type) {
+        return Err(AppError::public("加好友验证类型无效"));
+    }
+
     let conn = db::pool();
     let now = OffsetDateTime::now_utc();
 
@@ -451,6 +456,85 @@ pub async fn upsert_user_data(user_data: ImUserData) -> AppResult<()> {
     Ok(())
 }
 
+/// 管理员更新用户状态标识（封禁 / 禁言 / 禁止添加好友），未传的字段保持不变
+pub async fn update_user_flags(
+    user_id: &str,
+    forbidden_flag: Option<i32>,
+    silent_flag: Option<i32>,
+    disable_add_friend: Option<i32>,
+) -> AppResult<ImUserData> {
+    for flag in [forbidden_flag, silent_flag, disable_add_friend]
+        .into_iter()
+        .flatten()
+    {
+        if flag != 0 && flag != 1 {
+            return Err(AppError::public("标识只能为 0 或 1"));
+        }
+    }
+
+    // 确保用户数据存在（不存在时会自动创建默认数据）
+    get_user_data(user_id).await?;
+
+    let conn = db::pool();
+    let now = OffsetDateTime::now_utc();
+    sqlx::query!(
+        r#"UPDATE im_user_data SET
+                 forbidden_flag = COALESCE($2, forbidden_flag),
+                 silent_flag = COALESCE($3, silent_flag),
+                 disable_add_friend = COALESCE($4, disable_add_friend),
+                 update_time = $5,
+                 version = version + 1
+         WHERE user_id = $1 AND del_flag = 1"#,
+        user_id,
+        forbidden_flag,
+        silent_flag,
+        disable_add_friend,
+        now
+    )
+    .execute(conn)
+    .await?;
+
+    if USE_REDIS {
+        let _ = RedisClient::del(&cache_key_user_data(user_id)).await;
+    }
+
+    get_user_data(user_id).await
+}
+
+/// 被封禁（forbidden_flag = 1）的用户不能登录和访问接口
+pub async fn ensure_not_forbidden(user_id: &str) -> AppResult<()> {
+    match get_user_data(user_id).await {
+        Ok(data) if data.forbidden_flag == 1 => Err(AppError::unauthorized("账号已被封禁")),
+        Ok(_) | Err(AppError::NotFound(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// 被禁言（silent_flag = 1）的用户不能发送消息
+pub async fn ensure_not_silent(user_id: &str) -> AppResult<()> {
+    match get_user_data(user_id).await {
+        Ok(data) if data.silent_flag == 1 => Err(AppError::public("您已被禁言，无法发送消息")),
+        Ok(_) | Err(AppError::NotFound(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// 被管理员禁止添加好友（disable_add_friend = 1）的用户既不能发起也不能接收好友申请
+pub async fn ensure_can_add_friend(from_id: &str, to_id: &str) -> AppResult<()> {
+    match get_user_data(from_id).await {
+        Ok(data) if data.disable_add_friend == 1 => {
+            return Err(AppError::public("您已被禁止添加好友"));
+        }
+        Ok(_) | Err(AppError::NotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+    match get_user_data(to_id).await {
+        Ok(data) if data.disable_add_friend == 1 => Err(AppError::public("对方已被禁止添加好友")),
+        Ok(_) | Err(AppError::NotFound(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
 pub async fn verify_user(user_name: &str, password: &str) -> AppResult<ImSafeUser> {
     let user = get_by_user_name(user_name).await?;
 
@@ -1,9 +1,16 @@
 pub mod auth_service;
 pub mod friend_service;
 pub mod im_chat_service;
+pub mod im_friend_category_service;
 pub mod im_friendship_service;
+pub mod im_group_announcement_service;
+pub mod im_group_avatar_service;
+pub mod im_group_ban_service;
+pub mod im_group_invite_service;
 pub mod im_group_service;
+pub mod im_message_pin_service;
 pub mod im_message_service;
 pub mod im_outbox_service;
 pub mod im_user_service;
+pub mod totp_service;
 pub mod user_service;
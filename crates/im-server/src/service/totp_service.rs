@@ -0,0 +1,206 @@
+use crate::db;
+use crate::models::{User, UserTotp};
+use crate::prelude::*;
+use crate::utils::{self, totp};
+use im_share::redis::RedisClient;
+use rand::Rng;
+use time::OffsetDateTime;
+
+/// 恢复码数量
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+// Redis 缓存键生成函数
+fn cache_key_last_step(open_id: &str) -> String {
+    format!("auth:totp_last_step:{}", open_id)
+}
+
+pub async fn get_by_open_id(open_id: &str) -> AppResult<Option<UserTotp>> {
+    let conn = db::pool();
+    let row = sqlx::query_as!(
+        UserTotp,
+        r#"SELECT open_id, secret, enabled, create_time, update_time
+        FROM user_totp WHERE open_id = $1"#,
+        open_id
+    )
+    .fetch_optional(conn)
+    .await?;
+    Ok(row)
+}
+
+pub async fn is_enabled(open_id: &str) -> AppResult<bool> {
+    Ok(get_by_open_id(open_id)
+        .await?
+        .is_some_and(|row| row.enabled == 1))
+}
+
+/// 生成新的 TOTP 密钥（待确认状态），返回 Base32 密钥和 otpauth URI
+pub async fn enroll(user: &User, issuer: &str) -> AppResult<(String, String)> {
+    if is_enabled(&user.open_id).await? {
+        return Err(AppError::public("已开启两步验证"));
+    }
+
+    let secret = totp::base32_encode(&totp::generate_secret());
+    let now = OffsetDateTime::now_utc();
+
+    let conn = db::pool();
+    sqlx::query!(
+        r#"INSERT INTO user_totp (open_id, secret, enabled, create_time, update_time)
+        VALUES ($1, $2, 0, $3, $3)
+        ON CONFLICT (open_id) DO UPDATE SET secret = $2, enabled = 0, update_time = $3"#,
+        user.open_id,
+        secret,
+        now
+    )
+    .execute(conn)
+    .await?;
+
+    let uri = totp::otpauth_uri(issuer, &user.email, &secret);
+    Ok((secret, uri))
+}
+
+/// 使用验证码确认开启两步验证，返回一次性恢复码（明文仅返回这一次）
+pub async fn confirm(open_id: &str, code: &str) -> AppResult<Vec<String>> {
+    let row = get_by_open_id(open_id)
+        .await?
+        .ok_or_else(|| AppError::public("请先生成两步验证密钥"))?;
+    if row.enabled == 1 {
+        return Err(AppError::public("已开启两步验证"));
+    }
+
+    verify_totp(&row, code).await?;
+
+    let recovery_codes = generate_recovery_codes();
+    let mut code_hashes = Vec::with_capacity(recovery_codes.len());
+    for recovery_code in &recovery_codes {
+        code_hashes.push(im_share::hash_password(&normalize_recovery_code(
+            recovery_code,
+        ))?);
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let conn = db::pool();
+    let mut tx = conn.begin().await?;
+
+    sqlx::query!(
+        r#"UPDATE user_totp SET enabled = 1, update_time = $2 WHERE open_id = $1"#,
+        open_id,
+        now
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"DELETE FROM user_recovery_code WHERE open_id = $1"#,
+        open_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    for code_hash in &code_hashes {
+        sqlx::query!(
+            r#"INSERT INTO user_recovery_code (open_id, code_hash, create_time)
+            VALUES ($1, $2, $3)"#,
+            open_id,
+            code_hash,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(recovery_codes)
+}
+
+/// 校验两步验证码：TOTP 验证码或未使用的恢复码
+pub async fn verify_code(open_id: &str, code: &str) -> AppResult<()> {
+    let row = get_by_open_id(open_id)
+        .await?
+        .filter(|row| row.enabled == 1)
+        .ok_or_else(|| AppError::public("未开启两步验证"))?;
+
+    let code = code.trim();
+    if code.len() == totp::TOTP_DIGITS as usize && code.chars().all(|c| c.is_ascii_digit()) {
+        return verify_totp(&row, code).await;
+    }
+
+    verify_recovery_code(open_id, code).await
+}
+
+async fn verify_totp(row: &UserTotp, code: &str) -> AppResult<()> {
+    let secret = totp::base32_decode(&row.secret)
+        .ok_or_else(|| AppError::internal("Invalid totp secret"))?;
+    let step = totp::verify(&secret, code, utils::now_timestamp_seconds(), 1)
+        .ok_or_else(|| AppError::public("验证码错误"))?;
+
+    // 同一验证码在有效窗口内只能使用一次
+    let key = cache_key_last_step(&row.open_id);
+    if let Ok(Some(last_step)) = RedisClient::get(&key).await
+        && last_step
+            .parse::<i64>()
+            .is_ok_and(|last_step| step <= last_step)
+    {
+        return Err(AppError::public("验证码已使用"));
+    }
+    if let Err(e) =
+        RedisClient::set_with_ttl(&key, &step.to_string(), (totp::TOTP_PERIOD * 3) as u64).await
+    {
+        warn!(error = ?e, "记录 TOTP 使用步长失败");
+    }
+    Ok(())
+}
+
+async fn verify_recovery_code(open_id: &str, code: &str) -> AppResult<()> {
+    let code = normalize_recovery_code(code);
+    let conn = db::pool();
+    let rows = sqlx::query!(
+        r#"SELECT id, code_hash FROM user_recovery_code
+        WHERE open_id = $1 AND used_time IS NULL"#,
+        open_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    for row in rows {
+        if im_share::verify_password(&code, &row.code_hash).is_ok() {
+            let result = sqlx::query!(
+                r#"UPDATE user_recovery_code SET used_time = $2
+                WHERE id = $1 AND used_time IS NULL"#,
+                row.id,
+                OffsetDateTime::now_utc()
+            )
+            .execute(conn)
+            .await?;
+            if result.rows_affected() == 0 {
+                break;
+            }
+            info!(open_id = %open_id, "使用恢复码完成两步验证");
+            return Ok(());
+        }
+    }
+    Err(AppError::public("验证码错误"))
+}
+
+fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let chars: String = (0..10)
+                .map(|_| {
+                    RECOVERY_CODE_ALPHABET[rng.random_range(0..RECOVERY_CODE_ALPHABET.len())]
+                        as char
+                })
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect()
+}
+
+/// 恢复码忽略大小写和分隔符
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
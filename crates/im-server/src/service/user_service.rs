@@ -223,7 +223,7 @@ pub async fn get_by_phone(phone: &str) -> AppResult<User> {
 }
 
 pub async fn get_by_open_id(open_id: &str) -> AppResult<User> {
-    if let Some(user) = get_user_from_cache(open_id).await {
+    if let Some(user) = get_user_from_cache(&cache_key_open_id(open_id)).await {
         return Ok(user);
     }
 
@@ -436,8 +436,8 @@ async fn invaliddate_user_cache(user: &User) {
     let mut keys = vec![
         cache_key_user_id(user.id),
         cache_key_open_id(&user.open_id),
-        cache_key_open_id(&user.name),
-        cache_key_open_id(&user.email),
+        cache_key_name(&user.name),
+        cache_key_email(&user.email),
     ];
 
     if let Some(ref phone) = user.phone {
@@ -452,6 +452,32 @@ async fn invaliddate_user_cache(user: &User) {
     }
 }
 
+/// 校验密码格式
+pub fn validate_password(password: &str) -> AppResult<()> {
+    if password.len() < 6 {
+        return Err(AppError::public("密码长度不能少于6位"));
+    }
+    Ok(())
+}
+
+/// 更新密码（重新哈希）
+pub async fn update_password(user: &User, new_password: &str) -> AppResult<()> {
+    validate_password(new_password)?;
+    let password_hash = im_share::hash_password(new_password)?;
+
+    let conn = db::pool();
+    sqlx::query!(
+        r#"UPDATE users SET password_hash = $1 WHERE id = $2"#,
+        password_hash,
+        user.id
+    )
+    .execute(conn)
+    .await?;
+
+    invaliddate_user_cache(user).await;
+    Ok(())
+}
+
 pub async fn verify_user(email_or_name: &str, password: &str) -> AppResult<User> {
     let user = if email_or_name.contains('@') {
         get_by_email(email_or_name).await?
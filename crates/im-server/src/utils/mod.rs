@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 
+pub mod totp;
+
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::models::ChatMessage;
@@ -31,3 +33,28 @@ pub fn now_timestamp_seconds() -> i64 {
         .unwrap()
         .as_secs() as i64
 }
+
+/// 构造包含匹配的 LIKE/ILIKE 模式，转义其中的 %、_ 和 \，SQL 中需配合 ESCAPE '\' 使用
+pub fn like_contains_pattern(keyword: &str) -> String {
+    let mut pattern = String::with_capacity(keyword.len() + 2);
+    pattern.push('%');
+    for c in keyword.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_like_contains_pattern() {
+        assert_eq!(like_contains_pattern("abc"), "%abc%");
+        assert_eq!(like_contains_pattern("100%_a\\b"), "%100\\%\\_a\\\\b%");
+    }
+}
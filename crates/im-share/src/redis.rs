@@ -1,8 +1,15 @@
 use redis::{Client, aio::ConnectionManager};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::OnceLock;
 use tracing::info;
 
+/// 群成员缓存中记录成员数的标记字段，空群也写入该字段以便命中缓存
+const GROUP_MEMBERS_LOADED_FIELD: &str = "__loaded__";
+
+/// 群成员缓存版本号的过期时间（秒），需远大于成员缓存本身的过期时间
+const GROUP_MEMBERS_VERSION_TTL: u64 = 86400;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct RedisConfig {
     #[serde(default = "default_redis_host")]
@@ -150,6 +157,21 @@ impl RedisClient {
             .query_async(&mut conn)
             .await
     }
+
+    /// 计数器自增，首次创建时设置过期时间（单位：秒），返回自增后的值
+    pub async fn incr_with_ttl(key: &str, ttl: u64) -> Result<i64, redis::RedisError> {
+        let mut conn = RedisClient::get_connection();
+        let value: i64 = redis::cmd("INCR").arg(key).query_async(&mut conn).await?;
+        if value == 1 {
+            redis::cmd("EXPIRE")
+                .arg(key)
+                .arg(ttl)
+                .query_async::<()>(&mut conn)
+                .await?;
+        }
+        Ok(value)
+    }
+
     /// 添加离线消息到队列（使用 open_id）
     /// 使用 Redis List 存储，key: offline:message:{open_id}
     /// 使用 RPUSH 将新消息追加到列表末尾，确保消息按时间顺序（从旧到新）存储
@@ -208,6 +230,30 @@ impl RedisClient {
         Ok(())
     }
 
+    /// 批量添加离线消息，多个用户的 RPUSH 和 EXPIRE 合并为一次往返
+    pub async fn add_offline_messages(
+        open_ids: &[String],
+        message: &str,
+    ) -> Result<(), redis::RedisError> {
+        if open_ids.is_empty() {
+            return Ok(());
+        }
+        let mut conn = RedisClient::get_connection();
+        let mut pipe = redis::pipe();
+        for open_id in open_ids {
+            let key = format!("offline:message:{}", open_id);
+            pipe.cmd("RPUSH")
+                .arg(&key)
+                .arg(message)
+                .ignore()
+                .cmd("EXPIRE")
+                .arg(&key)
+                .arg(604800u64) // 7 * 24 * 60 * 60
+                .ignore();
+        }
+        pipe.query_async::<()>(&mut conn).await
+    }
+
     /// 获取并删除所有离线消息（使用 open_id）
     /// 返回消息列表，按时间顺序（从旧到新）
     /// LRANGE 0 -1 从左到右获取所有消息，由于使用 RPUSH，消息已按从旧到新顺序存储
@@ -356,4 +402,115 @@ impl RedisClient {
         }
         Ok(())
     }
+
+    // ========== 群成员缓存相关方法（使用 Redis Set + Hash） ==========
+
+    /// 读取群成员缓存，一次往返同时取回成员集合和成员解析映射
+    /// key: group:members:{group_id}（Set，成员ID），group:member_users:{group_id}（Hash，member_id -> 解析结果）
+    /// Hash 中的标记字段记录成员数，空群也会命中缓存；缓存不存在或不完整时返回 None
+    pub async fn get_group_members_cache(
+        group_id: &str,
+    ) -> Result<Option<(Vec<String>, HashMap<String, String>)>, redis::RedisError> {
+        let members_key = format!("group:members:{}", group_id);
+        let users_key = format!("group:member_users:{}", group_id);
+        let mut conn = RedisClient::get_connection();
+        let (members, mut users): (Vec<String>, HashMap<String, String>) = redis::pipe()
+            .cmd("SMEMBERS")
+            .arg(&members_key)
+            .cmd("HGETALL")
+            .arg(&users_key)
+            .query_async(&mut conn)
+            .await?;
+        let count = match users
+            .remove(GROUP_MEMBERS_LOADED_FIELD)
+            .and_then(|count| count.parse::<usize>().ok())
+        {
+            Some(count) => count,
+            None => return Ok(None),
+        };
+        // 集合与映射过期时间不一致时视为未命中
+        if members.len() != count {
+            return Ok(None);
+        }
+        Ok(Some((members, users)))
+    }
+
+    /// 读取群成员缓存版本号，回填缓存前需先取得版本号
+    pub async fn get_group_members_version(group_id: &str) -> Result<u64, redis::RedisError> {
+        let mut conn = RedisClient::get_connection();
+        let version: Option<u64> = redis::cmd("GET")
+            .arg(format!("group:members_version:{}", group_id))
+            .query_async(&mut conn)
+            .await?;
+        Ok(version.unwrap_or(0))
+    }
+
+    /// 写入群成员缓存（覆盖原有缓存）
+    /// 仅当版本号与加载前读取的一致时才写入，避免把失效前读到的旧成员集合写回；返回是否写入
+    pub async fn set_group_members_cache(
+        group_id: &str,
+        version: u64,
+        members: &[String],
+        users: &[(String, String)],
+        ttl: u64,
+    ) -> Result<bool, redis::RedisError> {
+        let script = redis::Script::new(
+            r#"
+            if (redis.call('GET', KEYS[3]) or '0') ~= ARGV[1] then
+                return 0
+            end
+            redis.call('DEL', KEYS[1], KEYS[2])
+            local count = tonumber(ARGV[3])
+            for i = 4, 3 + count do
+                redis.call('SADD', KEYS[1], ARGV[i])
+            end
+            redis.call('HSET', KEYS[2], ARGV[4 + count], count)
+            for i = 5 + count, #ARGV, 2 do
+                redis.call('HSET', KEYS[2], ARGV[i], ARGV[i + 1])
+            end
+            if count > 0 then
+                redis.call('EXPIRE', KEYS[1], ARGV[2])
+            end
+            redis.call('EXPIRE', KEYS[2], ARGV[2])
+            return 1
+            "#,
+        );
+        let mut invocation = script.prepare_invoke();
+        invocation
+            .key(format!("group:members:{}", group_id))
+            .key(format!("group:member_users:{}", group_id))
+            .key(format!("group:members_version:{}", group_id))
+            .arg(version)
+            .arg(ttl)
+            .arg(members.len())
+            .arg(members)
+            .arg(GROUP_MEMBERS_LOADED_FIELD);
+        for (field, value) in users {
+            invocation.arg(field).arg(value);
+        }
+        let mut conn = RedisClient::get_connection();
+        let written: i64 = invocation.invoke_async(&mut conn).await?;
+        Ok(written == 1)
+    }
+
+    /// 删除群成员缓存，同时递增版本号使并发中的回填失效
+    pub async fn del_group_members_cache(group_id: &str) -> Result<(), redis::RedisError> {
+        let version_key = format!("group:members_version:{}", group_id);
+        let mut conn = RedisClient::get_connection();
+        redis::pipe()
+            .atomic()
+            .cmd("INCR")
+            .arg(&version_key)
+            .ignore()
+            .cmd("EXPIRE")
+            .arg(&version_key)
+            .arg(GROUP_MEMBERS_VERSION_TTL)
+            .ignore()
+            .cmd("DEL")
+            .arg(format!("group:members:{}", group_id))
+            .arg(format!("group:member_users:{}", group_id))
+            .ignore()
+            .query_async(&mut conn)
+            .await
+    }
 }